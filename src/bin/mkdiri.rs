@@ -0,0 +1,32 @@
+//! The `mkdiri` command: creates a directory and writes its description in one step.
+//!
+//! `mkdiri PATH "description"` is the same as `lsi mkdiri PATH "description"`.
+
+#[macro_use]
+extern crate clap;
+
+use anyhow::Result;
+use clap::{App, Arg};
+use ls_improved::mkdiri;
+use ls_improved::path::LsiPathKind;
+
+/// Parses the command line and creates the directory.
+///
+/// # Errors
+///
+/// Returns an error if the directory cannot be created or its description cannot be written.
+fn main() -> Result<()> {
+    let yaml = load_yaml!("../mkdiri.yml");
+    // Under `lsi`, `-c` comes from the global option instead.
+    let args = App::from_yaml(yaml)
+        .arg(
+            Arg::with_name("config_path")
+                .short("c")
+                .long("config-path")
+                .value_name("ConfigPath")
+                .help("Load config from <ConfigPath>")
+                .takes_value(true),
+        )
+        .get_matches();
+    mkdiri::create(&mkdiri::create_args(&args, LsiPathKind::Dir), LsiPathKind::Dir)
+}
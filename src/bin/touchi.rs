@@ -0,0 +1,32 @@
+//! The `touchi` command: creates a file and writes its description in one step.
+//!
+//! `touchi PATH "description"` is the same as `lsi touchi PATH "description"`.
+
+#[macro_use]
+extern crate clap;
+
+use anyhow::Result;
+use clap::{App, Arg};
+use ls_improved::mkdiri;
+use ls_improved::path::LsiPathKind;
+
+/// Parses the command line and creates the file.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be created or its description cannot be written.
+fn main() -> Result<()> {
+    let yaml = load_yaml!("../touchi.yml");
+    // Under `lsi`, `-c` comes from the global option instead.
+    let args = App::from_yaml(yaml)
+        .arg(
+            Arg::with_name("config_path")
+                .short("c")
+                .long("config-path")
+                .value_name("ConfigPath")
+                .help("Load config from <ConfigPath>")
+                .takes_value(true),
+        )
+        .get_matches();
+    mkdiri::create(&mkdiri::create_args(&args, LsiPathKind::File), LsiPathKind::File)
+}
@@ -32,6 +32,8 @@ pub struct Colors {
     pub file: String,
     /// ANSI color code for descriptions.
     pub description: String,
+    /// ANSI color code for tag badges.
+    pub tag: String,
 }
 
 impl Colors {
@@ -75,6 +77,7 @@ impl Colors {
             "current_dir",
             "file",
             "description",
+            "tag",
         ];
         let mut ansi = HashMap::new();
         for term in terms.iter() {
@@ -114,6 +117,7 @@ impl Colors {
             "current_dir",
             "file",
             "description",
+            "tag",
         ];
         let mut ansi = HashMap::new();
         for term in terms.iter() {
@@ -146,6 +150,7 @@ impl Colors {
             "current_dir" => "\x1b[1;36m\x1b[4m",
             "file" => "\x1b[37m",
             "description" => "\x1b[33m",
+            "tag" => "\x1b[1;35m",
             _ => "",
         }
     }
@@ -174,6 +179,7 @@ impl Colors {
             current_dir: ansi["current_dir"].clone(),
             file: ansi["file"].clone(),
             description: ansi["description"].clone(),
+            tag: ansi["tag"].clone(),
         }
     }
 }
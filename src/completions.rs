@@ -0,0 +1,254 @@
+//! This module implements `lsi completions <SHELL>`, which prints a completion script
+//! generated from the clap definition, and the hidden `lsi complete <KIND> [PATH]` that
+//! the scripts call for candidates depending on the files and the config:
+//!
+//! - `-s, --set-description`: the existing description of the target path
+//! - `--template`: the templates of the config file given with `-c`
+//! - `--sort-by`, `--columns`: the description fields of the listed entries
+
+use crate::config::read_config;
+use crate::path::LsiPath;
+use crate::storage::{self, DescriptionStorage};
+use crate::{fs, lsi, LsiArgs};
+use anyhow::Result;
+use clap::{App, Shell};
+use regex::Regex;
+use std::collections::BTreeSet;
+use std::path::Path;
+
+/// Bash functions completing the dynamic values, wrapping the generated `_lsi`.
+const BASH_DYNAMIC: &str = r#"
+_lsi_dynamic() {
+    local cur prev word target config i
+    cur="${COMP_WORDS[COMP_CWORD]}"
+    prev="${COMP_WORDS[COMP_CWORD-1]}"
+    target=.
+    config=()
+    for ((i = 1; i < ${#COMP_WORDS[@]}; i++)); do
+        word="${COMP_WORDS[i]}"
+        case "${COMP_WORDS[i-1]}" in
+            -c|--config-path) config=(-c "${word}") ;;
+            -s|--set-description|--template|--sort-by|--columns|--where|--tag) ;;
+            *) [[ ${i} -ne ${COMP_CWORD} && "${word}" != -* && -e "${word}" && "${target}" == . ]] && target="${word}" ;;
+        esac
+    done
+    case "${prev}" in
+        -s|--set-description)
+            word="$(lsi "${config[@]}" complete description "${target}" 2>/dev/null)"
+            [[ -n "${word}" ]] && COMPREPLY=("$(printf '%q' "${word}")")
+            return 0
+            ;;
+        --template)
+            local IFS=$'\n'
+            COMPREPLY=($(compgen -W "$(lsi "${config[@]}" complete template 2>/dev/null)" -- "${cur}"))
+            return 0
+            ;;
+        --sort-by|--columns)
+            local IFS=$'\n'
+            COMPREPLY=($(compgen -W "$(lsi "${config[@]}" complete field "${target}" 2>/dev/null)" -- "${cur}"))
+            return 0
+            ;;
+    esac
+    _lsi "$@"
+}
+
+complete -F _lsi_dynamic -o bashdefault -o default lsi
+"#;
+
+/// Zsh functions completing the dynamic values, used as `_arguments` actions.
+const ZSH_DYNAMIC: &str = r#"
+_lsi_context() {
+    local i
+    _lsi_target=.
+    _lsi_config=()
+    for ((i = 2; i <= ${#words}; i++)); do
+        case "${words[i-1]}" in
+            -c|--config-path) _lsi_config=(-c "${words[i]}") ;;
+            -s|--set-description|--template|--sort-by|--columns|--where|--tag) ;;
+            *) [[ ${i} -ne ${CURRENT} && "${words[i]}" != -* && -e "${words[i]}" && "${_lsi_target}" == . ]] && _lsi_target="${words[i]}" ;;
+        esac
+    done
+}
+
+_lsi_description() {
+    local _lsi_target _lsi_config description
+    _lsi_context
+    description="$(lsi "${_lsi_config[@]}" complete description "${_lsi_target}" 2>/dev/null)"
+    [[ -n "${description}" ]] && compadd -Q -- "${(q)description}"
+}
+
+_lsi_templates() {
+    local _lsi_target _lsi_config
+    _lsi_context
+    compadd -- ${(f)"$(lsi "${_lsi_config[@]}" complete template 2>/dev/null)"}
+}
+
+_lsi_fields() {
+    local _lsi_target _lsi_config
+    _lsi_context
+    compadd -- ${(f)"$(lsi "${_lsi_config[@]}" complete field "${_lsi_target}" 2>/dev/null)"}
+}
+"#;
+
+/// Fish completions of the dynamic values, added to the generated ones.
+const FISH_DYNAMIC: &str = r#"
+function __lsi_complete
+    set -l tokens (commandline -opc)
+    set -l target .
+    set -l config
+    for i in (seq 2 (count $tokens))
+        switch $tokens[(math $i - 1)]
+            case -c --config-path
+                set config -c $tokens[$i]
+            case -s --set-description --template --sort-by --columns --where --tag
+            case '*'
+                if test $target = .; and not string match -q -- '-*' $tokens[$i]; and test -e $tokens[$i]
+                    set target $tokens[$i]
+                end
+        end
+    end
+    lsi $config complete $argv[1] $target 2>/dev/null
+end
+
+complete -c lsi -s s -l set-description -x -a '(__lsi_complete description | string escape)'
+complete -c lsi -l template -x -a '(__lsi_complete template)'
+complete -c lsi -l sort-by -x -a '(__lsi_complete field)'
+complete -c lsi -l columns -x -a '(__lsi_complete field)'
+"#;
+
+/// The options whose values are completed by the zsh functions above.
+const ZSH_ACTIONS: [(&str, &str, &str); 5] = [
+    ("-s", "Description", "_lsi_description"),
+    ("--set-description", "Description", "_lsi_description"),
+    ("--template", "Name", "_lsi_templates"),
+    ("--sort-by", "Key", "_lsi_fields"),
+    ("--columns", "Keys", "_lsi_fields"),
+];
+
+/// Prints the completion script for `shell`.
+///
+/// # Arguments
+///
+/// * `app` - The clap definition of `lsi` to generate the script from.
+/// * `shell` - `bash`, `zsh` or `fish`.
+///
+/// # Errors
+///
+/// Returns an error if the script cannot be generated.
+pub fn run(mut app: App, shell: &str) -> Result<()> {
+    let shell: Shell = shell.parse().map_err(anyhow::Error::msg)?;
+    let mut script = Vec::new();
+    app.gen_completions_to("lsi", shell, &mut script);
+    let mut script = String::from_utf8(script)?;
+
+    match shell {
+        Shell::Bash => script.push_str(BASH_DYNAMIC),
+        Shell::Zsh => {
+            for (option, value, action) in ZSH_ACTIONS {
+                script = with_zsh_action(&script, option, value, action);
+            }
+            // The generated script ends by calling `_lsi`, so the helpers go first.
+            script = script.replacen("_lsi() {", &format!("{}\n_lsi() {{", ZSH_DYNAMIC.trim()), 1);
+        }
+        Shell::Fish => script.push_str(FISH_DYNAMIC),
+        _ => {}
+    }
+    print!("{}", script);
+    Ok(())
+}
+
+/// Gives the zsh `_arguments` specs of `option` a value completed by `action`.
+fn with_zsh_action(script: &str, option: &str, value: &str, action: &str) -> String {
+    let spec = format!(r"(?m)^('(?:\([^)]*\))?\*?{}[+=]\[[^\]]*\])' \\$", regex::escape(option));
+    let re = Regex::new(&spec).unwrap_or_else(|_| Regex::new(r"^$").unwrap());
+    re.replace_all(script, format!(r"$1:{}:{}' \", value, action).as_str())
+        .to_string()
+}
+
+/// Prints the completion candidates of `kind`, one per line.
+///
+/// # Arguments
+///
+/// * `args` - A reference to the `LsiArgs` struct containing the target path and config.
+/// * `kind` - `description`, `template` or `field`.
+///
+/// # Errors
+///
+/// Returns an error if the storage backend cannot be opened.
+pub fn run_candidates(args: &LsiArgs, kind: &str) -> Result<()> {
+    let config = args.config_path.and_then(|path| read_config(path.to_string()));
+    let storage = storage::open(config.as_ref().and_then(|c| c.storage.as_ref()))?;
+    let path = Path::new(args.path);
+
+    match kind {
+        // Descriptions are given on one line, with `\n` for line breaks.
+        "description" => {
+            if let Ok(description) = storage.read(path) {
+                println!("{}", description.trim_end().replace('\n', "\\n"));
+            }
+        }
+        "template" => {
+            for name in config.iter().flat_map(|c| c.templates.iter().flatten()).map(|(name, _)| name) {
+                println!("{}", name);
+            }
+        }
+        _ => {
+            for key in field_keys(storage.as_ref(), path) {
+                println!("{}", key);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Collects the description field names of the entries of `path` (or of `path` itself if
+/// it is a file).
+fn field_keys(storage: &dyn DescriptionStorage, path: &Path) -> BTreeSet<String> {
+    let mut paths = match path.is_dir() {
+        true => fs::get_pathes(&path.to_string_lossy(), &None, &false, "p").unwrap_or_default(),
+        false => vec![LsiPath::new(path.to_path_buf(), "p")],
+    };
+    let _ = lsi::get_and_set_descriptions(storage, &mut paths);
+    paths.iter().flat_map(|p| p.get_fields().keys().cloned()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Generates the zsh script of the top-level options and the `ls` subcommand.
+    fn zsh_script() -> String {
+        let yaml = clap::load_yaml!("args.yml");
+        let ls_yaml = clap::load_yaml!("ls.yml");
+        let mut app = App::from_yaml(yaml).subcommand(App::from_yaml(ls_yaml));
+        let mut script = Vec::new();
+        app.gen_completions_to("lsi", Shell::Zsh, &mut script);
+        String::from_utf8(script).unwrap()
+    }
+
+    #[test]
+    fn with_zsh_action_completes_option_values() {
+        let mut script = zsh_script();
+        for (option, value, action) in ZSH_ACTIONS {
+            script = with_zsh_action(&script, option, value, action);
+        }
+
+        let lines: Vec<&str> = script.lines().collect();
+        let spec = |start: &str| lines.iter().find(|l| l.starts_with(start)).copied().unwrap_or_default();
+        assert!(spec("'-s+[").ends_with("]:Description:_lsi_description' \\"));
+        assert!(spec("'--set-description=[").ends_with("]:Description:_lsi_description' \\"));
+        // Specs excluding other options are matched as well.
+        assert!(spec("'(-s --set-description --from-stdin --remove-description)--template=[")
+            .ends_with("]:Name:_lsi_templates' \\"));
+        assert!(spec("'--sort-by=[").ends_with("]:Key:_lsi_fields' \\"));
+        assert!(spec("'--columns=[").ends_with("]:Keys:_lsi_fields' \\"));
+        // Other options are left alone.
+        assert!(spec("'(-s --set-description)--delete-line=[").ends_with("]' \\"));
+    }
+
+    #[test]
+    fn with_zsh_action_ignores_missing_options() {
+        let script = zsh_script();
+        assert_eq!(with_zsh_action(&script, "--no-such-option", "Value", "_lsi_fields"), script);
+    }
+}
@@ -2,6 +2,7 @@
 //! configuration settings and functions for reading and deserializing configuration files.
 
 use serde::Deserialize;
+use std::collections::BTreeMap;
 use std::fs;
 use std::io::{BufReader, Read};
 
@@ -10,6 +11,37 @@ use std::io::{BufReader, Read};
 pub struct Config {
     /// Optional color configuration settings.
     pub colors: Option<ColorConf>,
+    /// Optional description storage settings.
+    pub storage: Option<StorageConf>,
+    /// Optional description templates keyed by name.
+    pub templates: Option<BTreeMap<String, String>>,
+    /// Optional settings of the compact listing shown by the `lsi init` hook.
+    pub compact: Option<CompactConf>,
+    /// Optional listing display settings.
+    pub view: Option<ViewConf>,
+}
+
+/// Struct representing the listing display settings.
+#[derive(Debug, Deserialize)]
+pub struct ViewConf {
+    /// Name of the listing layout (`tree` by default, `oneline` or `grid`).
+    pub layout: Option<String>,
+}
+
+/// Struct representing the settings of the compact listing (`lsi --compact`).
+#[derive(Debug, Deserialize)]
+pub struct CompactConf {
+    /// Maximum number of lines to print (`10` by default, `0` for no limit).
+    pub max_lines: Option<usize>,
+}
+
+/// Struct representing the description storage settings.
+#[derive(Debug, Deserialize)]
+pub struct StorageConf {
+    /// Name of the storage backend (`sidecar` by default).
+    pub backend: Option<String>,
+    /// Whether to journal every change of a description (`false` by default).
+    pub history: Option<bool>,
 }
 
 /// Struct representing the color configuration settings.
@@ -28,6 +60,7 @@ pub struct ColorConf {
     pub current_dir: Option<Vec<String>>,
     pub file: Option<Vec<String>>,
     pub description: Option<Vec<String>>,
+    pub tag: Option<Vec<String>>,
 }
 
 /// Reads the content of a file and returns it as a string.
@@ -62,10 +95,7 @@ fn read_file(path: String) -> Result<String, String> {
 ///
 /// * An option containing the parsed `Config` struct, or `None` if the parsing fails.
 pub fn read_config(path: String) -> Option<Config> {
-    let s = match read_file(path) {
-        Ok(s) => s,
-        Err(_) => String::new(),
-    };
+    let s = read_file(path).unwrap_or_default();
 
     toml::from_str(&s).ok()
 }
@@ -95,6 +125,7 @@ impl ColorConf {
             "current_dir" => &self.current_dir,
             "file" => &self.file,
             "description" => &self.description,
+            "tag" => &self.tag,
             _ => &None,
         }
     }
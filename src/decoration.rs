@@ -18,6 +18,7 @@ use regex::Regex;
 /// * `colors` - A reference to `Colors` that holds various color codes.
 /// * `desc_num` - An optional reference to the number of description lines to process.
 /// * `is_last` - A reference to a boolean indicating if this is the last entry.
+/// * `columns_width` - The width of the field columns printed before the description.
 ///
 /// # Errors
 ///
@@ -27,10 +28,11 @@ pub fn run(
     colors: &Colors,
     desc_num: &Option<usize>,
     is_last: &bool,
+    columns_width: usize,
 ) -> Result<()> {
     let _ = replace_lsi_color_code(path, colors);
     let _ = replace_ansi_color_code(path);
-    let _ = format_multiline(path, colors, desc_num, is_last);
+    let _ = format_multiline(path, colors, desc_num, is_last, columns_width);
     Ok(())
 }
 
@@ -136,6 +138,7 @@ fn encolor_description(description: &str, colors: &Colors) -> String {
 /// * `colors` - A reference to `Colors` that holds various ANSI color codes.
 /// * `line_num` - An optional reference to the number of description lines to process.
 /// * `is_last` - A reference to a boolean indicating if this is the last entry.
+/// * `columns_width` - The width of the field columns printed before the description.
 ///
 /// # Errors
 ///
@@ -145,6 +148,7 @@ fn format_multiline(
     colors: &Colors,
     line_num: &Option<usize>,
     is_last: &bool,
+    columns_width: usize,
 ) -> Result<()> {
     let len = path.len();
     match path.get_description() {
@@ -167,10 +171,11 @@ fn format_multiline(
                 let tree_prefix = if *is_last { " " } else { "│" };
                 for d in desc.iter().take(num).skip(1) {
                     description = format!(
-                        "{}\n{}   {}\t  {}",
+                        "{}\n{}   {}\t{}  {}",
                         description,
                         tree_prefix,
                         " ".repeat(len),
+                        " ".repeat(columns_width),
                         encolor_description(d, colors)
                     );
                 }
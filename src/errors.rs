@@ -36,6 +36,30 @@ pub enum LsiError {
     #[error("Permission denied: Insufficient permissions to access {0}")]
     PermissionDenied(String),
     
+    /// Error indicating that a description line number is out of range.
+    #[error("Line {0} is out of range of the description")]
+    LineOutOfRange(usize),
+
+    /// Error indicating that the configured description storage backend does not exist.
+    #[error("Unknown storage backend: {0}")]
+    UnknownStorageBackend(String),
+
+    /// Error indicating that the requested description template is not configured.
+    #[error("Unknown template: {0} (define it in the [templates] section of the config file)")]
+    UnknownTemplate(String),
+
+    /// Error indicating that the configured listing layout does not exist.
+    #[error("Unknown layout: {0} (expected tree, oneline or grid)")]
+    UnknownLayout(String),
+
+    /// Error indicating a malformed listing filter.
+    #[error("Invalid filter: {0}")]
+    InvalidFilter(String),
+
+    /// Error indicating that watching for changes failed.
+    #[error("Failed to watch for changes: {0}")]
+    FailedWatch(String),
+
     /// Error indicating an I/O error.
     #[error("I/O error: {0}")]
     IoError(#[from] std::io::Error),
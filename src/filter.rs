@@ -0,0 +1,201 @@
+//! This module narrows a listing down to the paths matching the filters given on the
+//! command line: key/value predicates on the description fields (`--where 'acc>0.9'`),
+//! whether a description exists, a regex on the description text, and tags.
+
+use crate::errors::LsiError;
+use crate::metadata::{compare_values, parse_tags};
+use crate::path::LsiPath;
+use crate::LsiArgs;
+use anyhow::Result;
+use regex::Regex;
+use std::cmp::Ordering;
+
+/// Comparison operators of a `--where` predicate, longest first so `>=` wins over `>`.
+const OPERATORS: [&str; 7] = [">=", "<=", "!=", "==", ">", "<", "="];
+
+/// A `key<op>value` condition on a description field.
+#[derive(Debug, Clone)]
+pub struct Predicate {
+    /// The field name.
+    key: String,
+    /// The comparison operator.
+    op: &'static str,
+    /// The value to compare the field with.
+    value: String,
+}
+
+impl Predicate {
+    /// Parses a predicate such as `acc>0.9` or `model=resnet`.
+    ///
+    /// # Arguments
+    ///
+    /// * `expr` - The predicate expression.
+    ///
+    /// # Errors
+    ///
+    /// Returns `LsiError::InvalidFilter` if `expr` has no operator or an empty key.
+    pub fn parse(expr: &str) -> Result<Predicate> {
+        let (pos, op) = OPERATORS
+            .iter()
+            .filter_map(|op| expr.find(op).map(|pos| (pos, *op)))
+            .min_by_key(|(pos, op)| (*pos, usize::MAX - op.len()))
+            .ok_or_else(|| LsiError::InvalidFilter(expr.to_string()))?;
+        let key = expr[..pos].trim();
+        if key.is_empty() {
+            return Err(LsiError::InvalidFilter(expr.to_string()).into());
+        }
+        Ok(Predicate {
+            key: key.to_string(),
+            op,
+            value: expr[pos + op.len()..].trim().to_string(),
+        })
+    }
+
+    /// Checks the predicate against a path; paths without the field never match, and a
+    /// `nan` field only matches `!=`, `=` and `==`.
+    pub fn matches(&self, path: &LsiPath) -> bool {
+        let Some(field) = path.get_field(&self.key) else {
+            return false;
+        };
+        let is_nan = |v: &str| v.parse::<f64>().is_ok_and(f64::is_nan);
+        if (is_nan(field) || is_nan(&self.value)) && matches!(self.op, ">=" | "<=" | ">" | "<") {
+            return false;
+        }
+        let ordering = compare_values(field, &self.value);
+        match self.op {
+            ">=" => ordering != Ordering::Less,
+            "<=" => ordering != Ordering::Greater,
+            "!=" => ordering != Ordering::Equal,
+            ">" => ordering == Ordering::Greater,
+            "<" => ordering == Ordering::Less,
+            _ => ordering == Ordering::Equal,
+        }
+    }
+}
+
+/// The filters applied to a listing.
+#[derive(Debug, Default)]
+pub struct Filter {
+    /// Predicates that must all hold.
+    predicates: Vec<Predicate>,
+    /// Keep only paths with (`Some(true)`) or without (`Some(false)`) a description.
+    has_description: Option<bool>,
+    /// A regex the description text must match.
+    desc_match: Option<Regex>,
+    /// Tags that must all be present.
+    tags: Vec<String>,
+}
+
+impl Filter {
+    /// Builds the filter from the command-line arguments.
+    ///
+    /// # Arguments
+    ///
+    /// * `args` - A reference to the `LsiArgs` struct containing the current configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a predicate or the description regex is invalid.
+    pub fn new(args: &LsiArgs) -> Result<Filter> {
+        let predicates = args
+            .where_clauses
+            .iter()
+            .map(|expr| Predicate::parse(expr))
+            .collect::<Result<Vec<_>>>()?;
+        let desc_match = match args.desc_match {
+            Some(pattern) => Some(
+                Regex::new(pattern).map_err(|_| LsiError::InvalidFilter(pattern.to_string()))?,
+            ),
+            None => None,
+        };
+        Ok(Filter {
+            predicates,
+            has_description: args.has_description,
+            desc_match,
+            tags: args.tags.iter().flat_map(|t| parse_tags(t)).collect(),
+        })
+    }
+
+    /// Checks whether a path passes every filter.
+    pub fn matches(&self, path: &LsiPath) -> bool {
+        let has_description = path.get_description().is_some() || !path.get_fields().is_empty();
+        if self.has_description.is_some_and(|wanted| wanted != has_description) {
+            return false;
+        }
+        if let Some(re) = &self.desc_match {
+            match path.get_plain_description() {
+                Some(d) if re.is_match(&d) => {}
+                _ => return false,
+            }
+        }
+        if !self.tags.is_empty() {
+            let tags = path.get_tags();
+            if !self.tags.iter().all(|t| tags.contains(t)) {
+                return false;
+            }
+        }
+        self.predicates.iter().all(|p| p.matches(path))
+    }
+
+    /// Removes the paths that do not pass the filters.
+    pub fn apply(&self, paths: &mut Vec<LsiPath>) {
+        paths.retain(|path| self.matches(path));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use std::path::PathBuf;
+
+    fn path_with(key: &str, value: &str) -> LsiPath {
+        let mut path = LsiPath::new(PathBuf::from("run"), "p");
+        path.set_fields(BTreeMap::from([(key.to_string(), value.to_string())]));
+        path
+    }
+
+    #[test]
+    fn parse_prefers_the_longest_operator() {
+        let p = Predicate::parse("acc>=0.9").unwrap();
+        assert_eq!((p.key.as_str(), p.op, p.value.as_str()), ("acc", ">=", "0.9"));
+        let p = Predicate::parse("acc > 0.9").unwrap();
+        assert_eq!((p.key.as_str(), p.op, p.value.as_str()), ("acc", ">", "0.9"));
+        let p = Predicate::parse("model==resnet").unwrap();
+        assert_eq!((p.key.as_str(), p.op, p.value.as_str()), ("model", "==", "resnet"));
+        let p = Predicate::parse("model!=resnet").unwrap();
+        assert_eq!(p.op, "!=");
+    }
+
+    #[test]
+    fn parse_splits_at_the_first_operator() {
+        let p = Predicate::parse("note=a>b").unwrap();
+        assert_eq!((p.key.as_str(), p.op, p.value.as_str()), ("note", "=", "a>b"));
+    }
+
+    #[test]
+    fn parse_rejects_missing_operator_or_key() {
+        assert!(Predicate::parse("acc").is_err());
+        assert!(Predicate::parse(">0.9").is_err());
+        assert!(Predicate::parse(" =x").is_err());
+    }
+
+    #[test]
+    fn matches_compares_numbers_numerically() {
+        let path = path_with("acc", "0.95");
+        assert!(Predicate::parse("acc>0.9").unwrap().matches(&path));
+        assert!(Predicate::parse("acc>=0.95").unwrap().matches(&path));
+        assert!(!Predicate::parse("acc>0.95").unwrap().matches(&path));
+        assert!(Predicate::parse("acc<1e1").unwrap().matches(&path));
+        assert!(!Predicate::parse("lr>0").unwrap().matches(&path));
+    }
+
+    #[test]
+    fn matches_nan_only_for_equality() {
+        let path = path_with("acc", "nan");
+        assert!(!Predicate::parse("acc>0.9").unwrap().matches(&path));
+        assert!(!Predicate::parse("acc<=0.9").unwrap().matches(&path));
+        assert!(Predicate::parse("acc!=0.9").unwrap().matches(&path));
+        assert!(Predicate::parse("acc=nan").unwrap().matches(&path));
+    }
+}
@@ -4,10 +4,12 @@
 
 use crate::errors::LsiError;
 use crate::path::{LsiPath, LsiPathKind};
+use crate::storage::sidecar::FILE_DESCRIPTION_DIR;
+use crate::storage::DescriptionStorage;
 use anyhow::{Context, Result};
 use regex::Regex;
-use std::fs::{self, File};
-use std::io::{BufRead, BufReader, Write};
+use std::fs;
+use std::io;
 use std::path::{Path, PathBuf};
 
 /// Collects paths from the target directory and applies specified filters and sorting.
@@ -75,129 +77,354 @@ fn path_filter(path: &Path, is_only: &Option<LsiPathKind>, show_hidden: &bool) -
     }
 }
 
-/// Reads a directory description from a `.description.lsi` file located in the directory.
+/// Collects `root` and every directory below it.
+///
+/// Symbolic links are not followed and `.file_description_lsi` directories are skipped.
 ///
 /// # Arguments
 ///
-/// * `path` - A reference to an `LsiPath` representing the directory.
+/// * `root` - The directory to start from.
 ///
 /// # Errors
 ///
-/// Returns an error if the description file cannot be read.
+/// Returns an error if a directory cannot be read.
 ///
 /// # Returns
 ///
-/// A string containing the description read from the file.
-pub fn read_dir_description(path: &LsiPath) -> Result<String> {
-    let abs_path = path.absolute_path()?;
-    let desc_path = format!("{}/.description.lsi", abs_path);
-    read_description_file(&desc_path)
+/// A vector of directory paths, starting with `root`.
+pub fn walk_dirs(root: &Path) -> Result<Vec<PathBuf>> {
+    let mut dirs = vec![root.to_path_buf()];
+    let mut i = 0;
+    while i < dirs.len() {
+        let entries = fs::read_dir(&dirs[i])
+            .with_context(|| format!("Failed to read directory: {}", dirs[i].display()))?;
+        let mut children = Vec::new();
+        for entry in entries {
+            let entry = entry
+                .with_context(|| format!("Failed to read directory entry in {}", dirs[i].display()))?;
+            let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+            if is_dir && entry.file_name() != FILE_DESCRIPTION_DIR {
+                children.push(entry.path());
+            }
+        }
+        children.sort();
+        dirs.extend(children);
+        i += 1;
+    }
+    Ok(dirs)
 }
 
-/// Reads a file description from a `.file_description_lsi/.<filename>.lsi` file.
+/// Reads the description of a file or directory from the given storage backend.
 ///
 /// # Arguments
 ///
-/// * `path` - A reference to an `LsiPath` representing the file.
+/// * `storage` - The storage backend holding the descriptions.
+/// * `path` - A reference to an `LsiPath` representing the file or directory.
 ///
 /// # Errors
 ///
-/// Returns an error if the description file cannot be read.
+/// Returns an error if the path has no description or it cannot be read.
 ///
 /// # Returns
 ///
-/// A string containing the description read from the file.
-pub fn read_file_description(path: &LsiPath) -> Result<String> {
-    let abs_path = path.absolute_path()?;
-    let path_buf = PathBuf::from(&abs_path);
-    
-    let filename = path_buf.file_name()
-        .and_then(|name| name.to_str())
-        .ok_or_else(|| LsiError::InvalidPath)?;
-    
-    let parent = path_buf.parent()
-        .and_then(|p| p.to_str())
-        .ok_or_else(|| LsiError::InvalidPath)?;
-    
-    let desc_path = format!("{}/.file_description_lsi/.{}.lsi", parent, filename);
-    read_description_file(&desc_path)
+/// A string containing the description.
+pub fn read_description(storage: &dyn DescriptionStorage, path: &LsiPath) -> Result<String> {
+    storage.read(path.as_path())
 }
 
-/// Helper function to read a description file.
+/// Reads the description of a path that is about to be modified.
+///
+/// Only a missing description counts as empty: a description that exists but cannot be
+/// read (e.g. it is not valid UTF-8) is an error, so that it is never overwritten.
 ///
 /// # Arguments
 ///
-/// * `path` - The path to the description file.
+/// * `storage` - The storage backend holding the descriptions.
+/// * `path` - A reference to the `Path` representing the target path.
+///
+/// # Errors
+///
+/// Returns an error if the description exists but cannot be read.
 ///
 /// # Returns
 ///
-/// A Result containing the description as a String.
-fn read_description_file(path: &str) -> Result<String> {
-    let file = File::open(path)
-        .with_context(|| format!("Failed to open description file: {}", path))?;
-    
-    let reader = BufReader::new(file);
-    let content: Result<String> = reader.lines()
-        .collect::<std::result::Result<Vec<_>, _>>()
-        .map(|lines| lines.join("\n"))
-        .with_context(|| format!("Failed to read lines from description file: {}", path));
-    
-    Ok(content?.trim().to_string())
+/// The description, or an empty string if the path has none.
+pub fn read_current_description(storage: &dyn DescriptionStorage, path: &Path) -> Result<String> {
+    Ok(read_existing_description(storage, path)?.unwrap_or_default())
 }
 
-/// Writes a description to a file or directory description file.
+/// Reads the description of a path like `read_current_description`, telling a missing
+/// description apart from an empty one.
 ///
 /// # Arguments
 ///
+/// * `storage` - The storage backend holding the descriptions.
+/// * `path` - A reference to the `Path` representing the target path.
+///
+/// # Errors
+///
+/// Returns an error if the description exists but cannot be read.
+///
+/// # Returns
+///
+/// The description, or `None` if the path has none.
+pub fn read_existing_description(storage: &dyn DescriptionStorage, path: &Path) -> Result<Option<String>> {
+    match storage.read(path) {
+        Ok(content) => Ok(Some(content)),
+        Err(e) if is_not_found(&e) => Ok(None),
+        Err(e) => Err(e).with_context(|| format!("Failed to read description: {}", path.display())),
+    }
+}
+
+/// Checks whether an error means that there is no description.
+fn is_not_found(e: &anyhow::Error) -> bool {
+    let is_io_not_found = |e: &io::Error| e.kind() == io::ErrorKind::NotFound;
+    match e.downcast_ref::<LsiError>() {
+        Some(LsiError::DescriptionNotFound) => true,
+        Some(LsiError::IoError(e)) => is_io_not_found(e),
+        _ => e.downcast_ref::<io::Error>().is_some_and(is_io_not_found),
+    }
+}
+
+/// Writes a description to a file or directory through the given storage backend.
+///
+/// A literal `\n` in `content` (as typed on the command line) becomes a line break.
+///
+/// # Arguments
+///
+/// * `storage` - The storage backend holding the descriptions.
 /// * `path` - A reference to the `Path` representing the target path.
 /// * `content` - A string containing the description to write.
 ///
 /// # Errors
 ///
-/// Returns an error if the description file cannot be created or written to.
-pub fn write_description(path: &Path, content: String) -> Result<()> {
+/// Returns an error if the description cannot be stored.
+pub fn write_description(storage: &dyn DescriptionStorage, path: &Path, content: String) -> Result<()> {
     let content = Regex::new(r"\\n")
         .unwrap_or_else(|_| Regex::new(r"").unwrap())
         .replace_all(&content, "\n")
         .to_string();
+    let location = store_description(storage, path, &content)?;
+    println!("Success: Write description to {}", &location);
+    Ok(())
+}
 
-    let canonical_path = path.canonicalize()
-        .with_context(|| format!("Failed to canonicalize path: {}", path.display()))?;
-    
-    let filename = if path.is_dir() {
-        format!(
-            "{}/.description.lsi",
-            canonical_path.to_str().ok_or_else(|| LsiError::InvalidPath)?
-        )
-    } else {
-        let mut path_buf = PathBuf::from(canonical_path);
-        let filename = path_buf.file_name()
-            .and_then(|name| name.to_str())
-            .ok_or_else(|| LsiError::InvalidPath)?
-            .to_string();
-        
-        path_buf.pop();
-        path_buf.push(".file_description_lsi");
-        
-        if !path_buf.is_dir() {
-            fs::create_dir(&path_buf)
-                .with_context(|| format!("Failed to create directory: {}", path_buf.display()))?;
+/// Writes a description as it is, without printing where it went.
+///
+/// # Arguments
+///
+/// * `storage` - The storage backend holding the descriptions.
+/// * `path` - A reference to the `Path` representing the target path.
+/// * `content` - A string containing the description to write.
+///
+/// # Errors
+///
+/// Returns an error if the path does not exist or the description cannot be stored.
+///
+/// # Returns
+///
+/// The location the description was written to.
+pub fn store_description(storage: &dyn DescriptionStorage, path: &Path, content: &str) -> Result<String> {
+    if !path.exists() {
+        return Err(LsiError::PathNotFound)
+            .with_context(|| format!("Failed to write description: {}", path.display()));
+    }
+
+    storage.write(path, content)
+}
+
+/// An operation applied to the description of a path.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum DescriptionEdit {
+    /// Replace the whole description.
+    #[default]
+    Replace,
+    /// Add the text as new last line(s).
+    Append,
+    /// Add the text as new first line(s).
+    Prepend,
+    /// Replace line N (1-based), padding with empty lines if needed.
+    SetLine(usize),
+    /// Remove line N (1-based).
+    DeleteLine(usize),
+}
+
+impl DescriptionEdit {
+    /// Applies the operation to an existing description.
+    ///
+    /// # Arguments
+    ///
+    /// * `current` - The current description (empty if there is none).
+    /// * `text` - The text to add or set; ignored by `DeleteLine`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `LsiError::LineOutOfRange` if a line number is zero or, for `DeleteLine`,
+    /// beyond the last line.
+    ///
+    /// # Returns
+    ///
+    /// The edited description.
+    pub fn apply(&self, current: &str, text: &str) -> Result<String> {
+        let mut lines: Vec<&str> = match current.is_empty() {
+            true => Vec::new(),
+            false => current.split('\n').collect(),
+        };
+        match *self {
+            DescriptionEdit::Replace => return Ok(text.to_string()),
+            DescriptionEdit::Append => lines.push(text),
+            DescriptionEdit::Prepend => lines.insert(0, text),
+            DescriptionEdit::SetLine(n) => {
+                if n == 0 {
+                    return Err(LsiError::LineOutOfRange(n).into());
+                }
+                if lines.len() < n {
+                    lines.resize(n, "");
+                }
+                lines[n - 1] = text;
+            }
+            DescriptionEdit::DeleteLine(n) => {
+                if n == 0 || n > lines.len() {
+                    return Err(LsiError::LineOutOfRange(n).into());
+                }
+                lines.remove(n - 1);
+            }
         }
-        
-        format!(
-            "{}/.{}.lsi", 
-            path_buf.to_str().ok_or_else(|| LsiError::InvalidPath)?, 
-            filename
-        )
-    };
-
-    let mut file = File::create(&filename)
-        .with_context(|| format!("Failed to create description file: {}", filename))?;
-
-    file.write_all(content.as_bytes())
-        .with_context(|| format!("Failed to write to description file: {}", filename))?;
-    
-    println!("Success: Write description to {}", &filename);
+        Ok(lines.join("\n"))
+    }
+}
 
+/// Applies an edit operation to the description of a file or directory.
+///
+/// A literal `\n` in `text` becomes a line break, while the existing description is
+/// kept as it is. A description that becomes empty (e.g. after deleting its only line)
+/// is removed. The description is locked while it is read, edited and written back.
+///
+/// # Arguments
+///
+/// * `storage` - The storage backend holding the descriptions.
+/// * `path` - A reference to the `Path` representing the target path.
+/// * `edit` - The operation to apply.
+/// * `text` - The text used by the operation.
+///
+/// # Errors
+///
+/// Returns an error if the edit is invalid, or the description cannot be read or stored.
+pub fn edit_description(
+    storage: &dyn DescriptionStorage,
+    path: &Path,
+    edit: DescriptionEdit,
+    text: &str,
+) -> Result<()> {
+    if edit == DescriptionEdit::Replace {
+        return write_description(storage, path, text.to_string());
+    }
+
+    let text = text.replace("\\n", "\n");
+    // Hold the lock across the read-modify-write so concurrent edits do not interleave.
+    let _lock = storage.lock(path)?;
+    let current = read_current_description(storage, path)?;
+    let content = edit
+        .apply(&current, &text)
+        .with_context(|| format!("Failed to edit description: {}", path.display()))?;
+
+    if content.trim().is_empty() {
+        return remove_description(storage, path);
+    }
+    let location = store_description(storage, path, &content)?;
+    println!("Success: Write description to {}", &location);
+    Ok(())
+}
+
+/// Removes the description of a file or directory.
+///
+/// With the sidecar backend, `.file_description_lsi` is removed as well once it is empty.
+///
+/// # Arguments
+///
+/// * `storage` - The storage backend holding the descriptions.
+/// * `path` - A reference to the `Path` representing the target path.
+///
+/// # Errors
+///
+/// Returns an error if the path has no description or it cannot be removed.
+pub fn remove_description(storage: &dyn DescriptionStorage, path: &Path) -> Result<()> {
+    storage
+        .delete(path)
+        .with_context(|| format!("Failed to remove description: {}", path.display()))?;
+    println!("Success: Remove description of {}", path.display());
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::sidecar::SidecarStorage;
+    use crate::testing::{touch, with_unreadable_description, UNREADABLE};
+
+    #[test]
+    fn apply_replace_append_prepend() {
+        assert_eq!(DescriptionEdit::Replace.apply("a\nb", "c").unwrap(), "c");
+        assert_eq!(DescriptionEdit::Append.apply("a\nb", "c").unwrap(), "a\nb\nc");
+        assert_eq!(DescriptionEdit::Prepend.apply("a\nb", "c").unwrap(), "c\na\nb");
+        assert_eq!(DescriptionEdit::Append.apply("", "c").unwrap(), "c");
+        assert_eq!(DescriptionEdit::Prepend.apply("", "c").unwrap(), "c");
+    }
+
+    #[test]
+    fn apply_set_line_pads_missing_lines() {
+        assert_eq!(DescriptionEdit::SetLine(2).apply("a\nb\nc", "x").unwrap(), "a\nx\nc");
+        assert_eq!(DescriptionEdit::SetLine(4).apply("a", "x").unwrap(), "a\n\n\nx");
+        assert_eq!(DescriptionEdit::SetLine(1).apply("", "x").unwrap(), "x");
+    }
+
+    #[test]
+    fn apply_delete_line() {
+        assert_eq!(DescriptionEdit::DeleteLine(1).apply("a\nb", "").unwrap(), "b");
+        assert_eq!(DescriptionEdit::DeleteLine(2).apply("a\nb", "").unwrap(), "a");
+        assert_eq!(DescriptionEdit::DeleteLine(1).apply("a", "").unwrap(), "");
+    }
+
+    #[test]
+    fn apply_rejects_line_zero_and_out_of_range() {
+        for edit in [DescriptionEdit::SetLine(0), DescriptionEdit::DeleteLine(0), DescriptionEdit::DeleteLine(3)] {
+            let err = edit.apply("a\nb", "x").unwrap_err();
+            assert!(matches!(err.downcast_ref::<LsiError>(), Some(LsiError::LineOutOfRange(_))));
+        }
+        assert!(DescriptionEdit::DeleteLine(1).apply("", "").is_err());
+    }
+
+    #[test]
+    fn store_description_keeps_escaped_line_breaks() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = touch(dir.path(), "a.txt");
+        store_description(&SidecarStorage, &file, r"path C:\new\data").unwrap();
+        assert_eq!(SidecarStorage.read(&file).unwrap(), r"path C:\new\data");
+    }
+
+    #[test]
+    fn edit_description_unescapes_only_the_added_text() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = touch(dir.path(), "a.txt");
+        store_description(&SidecarStorage, &file, r"path C:\new\data").unwrap();
+
+        edit_description(&SidecarStorage, &file, DescriptionEdit::Append, r"a\nb").unwrap();
+        assert_eq!(SidecarStorage.read(&file).unwrap(), "path C:\\new\\data\na\nb");
+    }
+
+    #[test]
+    fn edit_description_keeps_an_unreadable_description() {
+        let dir = tempfile::tempdir().unwrap();
+        let (file, sidecar) = with_unreadable_description(dir.path(), "a.txt");
+
+        assert!(edit_description(&SidecarStorage, &file, DescriptionEdit::Append, "val_acc=0.91").is_err());
+        assert_eq!(std::fs::read(&sidecar).unwrap(), UNREADABLE);
+    }
+
+    #[test]
+    fn read_current_description_treats_missing_as_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = touch(dir.path(), "a.txt");
+        assert_eq!(read_current_description(&SidecarStorage, &file).unwrap(), "");
+    }
+}
@@ -0,0 +1,234 @@
+//! This module computes the git status of listed entries from the local repository.
+//!
+//! The status is read with `git status --porcelain`, which only looks at the working
+//! tree and the index and never contacts a remote. A directory takes the most notable
+//! status of the entries below it.
+
+use crate::colors::Colors;
+use crate::path::LsiPath;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// The git status of an entry, from least to most notable.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
+pub enum GitStatus {
+    /// Tracked and unchanged.
+    Clean,
+    /// Ignored by `.gitignore`.
+    Ignored,
+    /// Not tracked.
+    Untracked,
+    /// Changes are staged in the index.
+    Staged,
+    /// Changed in the working tree.
+    Modified,
+}
+
+impl GitStatus {
+    /// Parses the `XY` code of a `git status --porcelain` entry.
+    fn from_code(code: &[u8]) -> GitStatus {
+        match code {
+            b"??" => GitStatus::Untracked,
+            b"!!" => GitStatus::Ignored,
+            [_, y] if *y != b' ' => GitStatus::Modified,
+            _ => GitStatus::Staged,
+        }
+    }
+
+    /// Formats the status as a one-character colored marker.
+    ///
+    /// # Arguments
+    ///
+    /// * `colors` - A reference to the `Colors` struct for controlling the display colors.
+    ///
+    /// # Returns
+    ///
+    /// `M` (modified), `S` (staged), `?` (untracked), `!` (ignored) or a space (clean).
+    pub fn marker(&self, colors: &Colors) -> String {
+        let (color, marker) = match self {
+            GitStatus::Modified => (&colors.red, "M"),
+            GitStatus::Staged => (&colors.green, "S"),
+            GitStatus::Untracked => (&colors.purple, "?"),
+            GitStatus::Ignored => (&colors.white, "!"),
+            GitStatus::Clean => return " ".to_string(),
+        };
+        format!("{}{}{}", color, marker, colors.end)
+    }
+}
+
+/// Sets the git status of each path that lies inside a git repository.
+///
+/// `git status` runs once per parent directory.
+///
+/// # Arguments
+///
+/// * `paths` - The paths to annotate.
+pub fn set_statuses(paths: &mut [LsiPath]) {
+    let mut cache: HashMap<PathBuf, Option<DirStatuses>> = HashMap::new();
+    for path in paths.iter_mut() {
+        let Some(absolute) = absolute_path(path.as_path()) else {
+            continue;
+        };
+        let Some(dir) = absolute.parent() else {
+            continue;
+        };
+        let statuses = cache
+            .entry(dir.to_path_buf())
+            .or_insert_with(|| dir_statuses(dir));
+        if let Some(statuses) = statuses {
+            let status = statuses.entries.get(&absolute).copied().unwrap_or(statuses.rest);
+            path.set_git_status(status);
+        }
+    }
+}
+
+/// Makes `path` absolute without resolving its last component, so a symlink keeps its own
+/// status instead of its target's.
+fn absolute_path(path: &Path) -> Option<PathBuf> {
+    let (Some(parent), Some(name)) = (path.parent(), path.file_name()) else {
+        return path.canonicalize().ok();
+    };
+    let parent = match parent.as_os_str().is_empty() {
+        true => Path::new("."),
+        false => parent,
+    };
+    Some(parent.canonicalize().ok()?.join(name))
+}
+
+/// The git statuses of the entries of a directory.
+#[derive(Debug, PartialEq)]
+struct DirStatuses {
+    /// The entries that are not clean, by path.
+    entries: HashMap<PathBuf, GitStatus>,
+    /// The status of every other entry: `Clean`, or the status of an untracked or ignored
+    /// directory that git reports as a whole and that contains the directory.
+    rest: GitStatus,
+}
+
+/// Collects the status of the entries of `dir` that are not clean.
+///
+/// # Arguments
+///
+/// * `dir` - The (canonical) directory whose entries are looked up.
+///
+/// # Returns
+///
+/// The statuses, or `None` if `dir` is not inside a git repository.
+fn dir_statuses(dir: &Path) -> Option<DirStatuses> {
+    let root = git(dir, &["rev-parse", "--show-toplevel"])?;
+    let root = PathBuf::from(String::from_utf8_lossy(&root).trim());
+    let output = git(dir, &["status", "--porcelain=v1", "-z", "--ignored", "--", "."])?;
+    Some(parse_statuses(&output, &root, dir))
+}
+
+/// Parses the output of `git status --porcelain=v1 -z` into the statuses of the entries
+/// of `dir`.
+///
+/// # Arguments
+///
+/// * `output` - The NUL-separated records, with paths relative to `root`.
+/// * `root` - The top-level directory of the repository.
+/// * `dir` - The directory whose entries are looked up.
+fn parse_statuses(output: &[u8], root: &Path, dir: &Path) -> DirStatuses {
+    let mut statuses = DirStatuses {
+        entries: HashMap::new(),
+        rest: GitStatus::Clean,
+    };
+    let mut records = output.split(|b| *b == 0);
+    while let Some(record) = records.next() {
+        if record.len() < 4 {
+            continue;
+        }
+        let (code, name) = (&record[..2], &record[3..]);
+        // Renames and copies are followed by their original path.
+        if matches!(code[0], b'R' | b'C') {
+            records.next();
+        }
+        let path = root.join(String::from_utf8_lossy(name).trim_end_matches('/'));
+        let status = GitStatus::from_code(code);
+        // An untracked or ignored directory is reported as a whole, also when listing inside it.
+        if dir.starts_with(&path) {
+            statuses.rest = statuses.rest.max(status);
+            continue;
+        }
+        let Ok(relative) = path.strip_prefix(dir) else {
+            continue;
+        };
+        let Some(child) = relative.components().next() else {
+            continue;
+        };
+        let entry = statuses.entries.entry(dir.join(child)).or_insert(status);
+        *entry = (*entry).max(status);
+    }
+    statuses
+}
+
+/// Runs a git command in `dir`, returning its output if it succeeds.
+fn git(dir: &Path, args: &[&str]) -> Option<Vec<u8>> {
+    Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| output.stdout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_statuses_takes_the_most_notable_status_per_child() {
+        let output = b" M sub/a.txt\0A  sub/b.txt\0?? c.txt\0!! build/\0MM d.txt\0";
+        let statuses = parse_statuses(output, Path::new("/r"), Path::new("/r"));
+        assert_eq!(statuses.rest, GitStatus::Clean);
+        assert_eq!(
+            statuses.entries,
+            HashMap::from([
+                (PathBuf::from("/r/sub"), GitStatus::Modified),
+                (PathBuf::from("/r/c.txt"), GitStatus::Untracked),
+                (PathBuf::from("/r/build"), GitStatus::Ignored),
+                (PathBuf::from("/r/d.txt"), GitStatus::Modified),
+            ])
+        );
+    }
+
+    #[test]
+    fn parse_statuses_skips_the_original_path_of_renames_and_copies() {
+        let output = b"R  new.txt\0old.txt\0C  copy.txt\0orig.txt\0 M a.txt\0";
+        let statuses = parse_statuses(output, Path::new("/r"), Path::new("/r"));
+        assert_eq!(
+            statuses.entries,
+            HashMap::from([
+                (PathBuf::from("/r/new.txt"), GitStatus::Staged),
+                (PathBuf::from("/r/copy.txt"), GitStatus::Staged),
+                (PathBuf::from("/r/a.txt"), GitStatus::Modified),
+            ])
+        );
+    }
+
+    #[test]
+    fn parse_statuses_gives_children_of_a_reported_directory_its_status() {
+        let output = b"?? newdir/\0!! build/\0";
+        for (dir, status) in [
+            ("/r/newdir", GitStatus::Untracked),
+            ("/r/newdir/deep", GitStatus::Untracked),
+            ("/r/build", GitStatus::Ignored),
+        ] {
+            let statuses = parse_statuses(output, Path::new("/r"), Path::new(dir));
+            assert!(statuses.entries.is_empty());
+            assert_eq!(statuses.rest, status);
+        }
+    }
+
+    #[test]
+    fn parse_statuses_ignores_paths_outside_of_dir() {
+        let output = b" M other/a.txt\0?? new\0";
+        let statuses = parse_statuses(output, Path::new("/r"), Path::new("/r/sub"));
+        assert!(statuses.entries.is_empty());
+        assert_eq!(statuses.rest, GitStatus::Clean);
+    }
+}
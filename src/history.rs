@@ -0,0 +1,111 @@
+//! This module implements `lsi history`, which shows the recorded changes of a
+//! description, and `lsi restore`, which rolls a description back to an earlier text.
+//!
+//! Changes are only recorded with `history = true` in the `[storage]` section of the
+//! configuration file (see `storage::history`).
+
+use crate::colors::Colors;
+use crate::config::read_config;
+use crate::errors::LsiError;
+use crate::fs::remove_description;
+use crate::storage::history::{read_history, HistoryEntry};
+use crate::storage::{self, DescriptionStorage};
+use crate::LsiArgs;
+use anyhow::{Context, Result};
+use std::path::Path;
+
+/// Runs `lsi history` based on the provided arguments.
+///
+/// The current description is printed first, followed by the recorded changes,
+/// latest first and numbered as `lsi restore` expects them.
+///
+/// # Arguments
+///
+/// * `args` - A reference to the `LsiArgs` struct containing the current configuration.
+///
+/// # Errors
+///
+/// Returns an error if the path does not exist or the history cannot be read.
+pub fn run_history(args: &LsiArgs) -> Result<()> {
+    let config = args.config_path.and_then(|path| read_config(path.to_string()));
+    let colors = Colors::new(config.as_ref().and_then(|c| c.colors.as_ref()));
+    let storage = storage::open(config.as_ref().and_then(|c| c.storage.as_ref()))?;
+    let path = existing_path(args.path)?;
+
+    println!("{}current{}", colors.green, colors.end);
+    print_description(storage.read(path).ok().as_deref());
+
+    let entries = read_history(path)?;
+    if entries.is_empty() {
+        println!("No recorded changes of {}", path.display());
+    }
+    for (i, entry) in entries.iter().rev().enumerate() {
+        println!(
+            "{}#{}{}  {}  {}",
+            colors.yellow,
+            i + 1,
+            colors.end,
+            entry.time,
+            entry.user
+        );
+        print_description(entry.previous.as_deref());
+    }
+    Ok(())
+}
+
+/// Runs `lsi restore` based on the provided arguments.
+///
+/// The description is set back to the text it had before change `N` (1 = the latest
+/// change, as numbered by `lsi history`). Restoring is itself a change, so it can be undone.
+///
+/// # Arguments
+///
+/// * `args` - A reference to the `LsiArgs` struct containing the current configuration.
+///
+/// # Errors
+///
+/// Returns an error if the path does not exist, has no such change, or cannot be written.
+pub fn run_restore(args: &LsiArgs) -> Result<()> {
+    let config = args.config_path.and_then(|path| read_config(path.to_string()));
+    let storage = storage::open(config.as_ref().and_then(|c| c.storage.as_ref()))?;
+    let path = existing_path(args.path)?;
+
+    let n = args.history_index.unwrap_or(1);
+    let entries = read_history(path)?;
+    let entry = n
+        .checked_sub(1)
+        .and_then(|i| entries.iter().rev().nth(i))
+        .ok_or_else(|| LsiError::FileOperationFailed(format!("{} has no recorded change #{}", path.display(), n)))?;
+    restore(storage.as_ref(), path, entry)
+}
+
+/// Sets the description of `path` back to the text before `entry`.
+///
+/// # Errors
+///
+/// Returns an error if the description cannot be written or removed.
+fn restore(storage: &dyn DescriptionStorage, path: &Path, entry: &HistoryEntry) -> Result<()> {
+    match &entry.previous {
+        Some(previous) => storage
+            .write(path, previous)
+            .map(|location| println!("Success: Restore description of {} from {}", location, entry.time)),
+        None => remove_description(storage, path),
+    }
+}
+
+/// Checks that `path` exists.
+fn existing_path(path: &str) -> Result<&Path> {
+    let path = Path::new(path);
+    if !path.exists() {
+        return Err(LsiError::PathNotFound).with_context(|| format!("Failed to read history: {}", path.display()));
+    }
+    Ok(path)
+}
+
+/// Prints a description indented under its heading.
+fn print_description(description: Option<&str>) {
+    match description {
+        Some(d) => d.lines().for_each(|line| println!("    {}", line)),
+        None => println!("    (no description)"),
+    }
+}
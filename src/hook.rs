@@ -0,0 +1,85 @@
+//! This module implements `lsi init <SHELL>`, which prints a shell hook that runs
+//! `lsi --compact .` whenever the current directory changes.
+//!
+//! The hook is meant to be evaluated from the shell's startup file, so everyone gets the
+//! same behaviour instead of hand-written `cd` aliases.
+
+use crate::LsiArgs;
+use anyhow::{Context, Result};
+use std::path::Path;
+
+/// The bash hook, run from `PROMPT_COMMAND` so that `cd`, `pushd`, `popd` and friends all
+/// trigger it.
+const BASH_HOOK: &str = r#"__lsi_hook() {
+    if [[ "${__lsi_pwd}" != "${PWD}" ]]; then
+        __lsi_pwd="${PWD}"
+        command lsi{config} --compact .
+    fi
+}
+__lsi_pwd="${PWD}"
+if [[ ";${PROMPT_COMMAND:-};" != *";__lsi_hook;"* ]]; then
+    PROMPT_COMMAND="__lsi_hook${PROMPT_COMMAND:+;${PROMPT_COMMAND}}"
+fi
+"#;
+
+/// The zsh hook, registered as a `chpwd` function.
+const ZSH_HOOK: &str = r#"__lsi_hook() {
+    command lsi{config} --compact .
+}
+autoload -Uz add-zsh-hook
+add-zsh-hook chpwd __lsi_hook
+"#;
+
+/// The fish hook, run when `PWD` changes.
+const FISH_HOOK: &str = r#"function __lsi_hook --on-variable PWD
+    status --is-command-substitution; and return
+    command lsi{config} --compact .
+end
+"#;
+
+/// Prints the directory change hook for `shell`.
+///
+/// The config file given with `-c` is passed on to the hook.
+///
+/// # Arguments
+///
+/// * `args` - A reference to the `LsiArgs` struct containing the config path.
+/// * `shell` - `bash`, `zsh` or `fish`.
+///
+/// # Errors
+///
+/// Returns an error if the config file does not exist.
+pub fn run_init(args: &LsiArgs, shell: &str) -> Result<()> {
+    let config = match args.config_path {
+        Some(path) => {
+            let path = Path::new(path)
+                .canonicalize()
+                .with_context(|| format!("Failed to find config file: {}", path))?;
+            format!(" -c {}", quote(&path.to_string_lossy()))
+        }
+        None => String::new(),
+    };
+    let hook = match shell {
+        "zsh" => ZSH_HOOK,
+        "fish" => FISH_HOOK,
+        _ => BASH_HOOK,
+    };
+    print!("{}", hook.replace("{config}", &config));
+    Ok(())
+}
+
+/// Quotes `s` for the shell with single quotes.
+fn quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quote_escapes_single_quotes() {
+        assert_eq!(quote("/home/me/.lsi.toml"), "'/home/me/.lsi.toml'");
+        assert_eq!(quote("it's here"), r"'it'\''s here'");
+    }
+}
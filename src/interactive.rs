@@ -0,0 +1,328 @@
+//! This module implements the interactive mode (`lsi -i`): a terminal browser over the
+//! listing of `fs::get_pathes`, with a preview of the selected description and in-place
+//! description editing.
+//!
+//! The screen is drawn on stderr, so the directory printed on stdout when quitting with
+//! `q` can be captured by a shell function such as `cd "$(lsi -i)"`.
+
+use crate::filter::Filter;
+use crate::path::LsiPath;
+use crate::storage::DescriptionStorage;
+use crate::{fs, lsi, LsiArgs};
+use anyhow::{Context, Result};
+use ratatui::backend::CrosstermBackend;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::crossterm::execute;
+use ratatui::crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{Frame, Terminal};
+use std::io::{self, Stderr};
+use std::path::{Path, PathBuf};
+use tui_textarea::TextArea;
+
+/// The key bindings shown in the status line while browsing.
+const BROWSE_HELP: &str = "↑↓ move  → enter  ← parent  e edit  q quit (print dir)  Esc cancel";
+/// The key bindings shown in the status line while editing.
+const EDIT_HELP: &str = "Ctrl-S save  Esc cancel  (an empty description is removed)";
+
+/// Runs the interactive browser, starting at `args.path`.
+///
+/// # Arguments
+///
+/// * `storage` - The storage backend holding the descriptions.
+/// * `filter` - The filters selecting the entries to show.
+/// * `args` - A reference to the `LsiArgs` struct containing the current configuration.
+///
+/// # Errors
+///
+/// Returns an error if the starting directory cannot be read or the terminal cannot be set up.
+pub fn run(storage: &dyn DescriptionStorage, filter: &Filter, args: &LsiArgs) -> Result<()> {
+    let dir = Path::new(args.path)
+        .canonicalize()
+        .with_context(|| format!("Failed to get paths from: {}", args.path))?;
+    let mut browser = Browser::new(storage, filter, args, dir)?;
+
+    enable_raw_mode()?;
+    execute!(io::stderr(), EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(io::stderr()))?;
+    let result = browser.event_loop(&mut terminal);
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+
+    if let Some(dir) = result? {
+        println!("{}", dir.display());
+    }
+    Ok(())
+}
+
+/// The state of the interactive browser.
+struct Browser<'a> {
+    /// The storage backend holding the descriptions.
+    storage: &'a dyn DescriptionStorage,
+    /// The filters selecting the entries to show.
+    filter: &'a Filter,
+    /// The listing options.
+    args: &'a LsiArgs<'a>,
+    /// The (canonical) directory being browsed.
+    dir: PathBuf,
+    /// The entries of `dir`, with their descriptions.
+    entries: Vec<LsiPath>,
+    /// The selected entry.
+    state: ListState,
+    /// The description being edited, if any.
+    editor: Option<TextArea<'static>>,
+    /// The outcome of the last action, shown in the status line.
+    message: Option<String>,
+}
+
+impl<'a> Browser<'a> {
+    /// Creates a browser showing `dir`.
+    fn new(
+        storage: &'a dyn DescriptionStorage,
+        filter: &'a Filter,
+        args: &'a LsiArgs<'a>,
+        dir: PathBuf,
+    ) -> Result<Browser<'a>> {
+        let mut browser = Browser {
+            storage,
+            filter,
+            args,
+            dir,
+            entries: Vec::new(),
+            state: ListState::default(),
+            editor: None,
+            message: None,
+        };
+        browser.load(None)?;
+        Ok(browser)
+    }
+
+    /// Reads the entries of the current directory and selects `selected`, or the first entry.
+    fn load(&mut self, selected: Option<&Path>) -> Result<()> {
+        let dir = self.dir.to_string_lossy();
+        let mut entries = fs::get_pathes(&dir, &self.args.is_only, &self.args.show_hidden, &self.args.sort_mode)
+            .with_context(|| format!("Failed to get paths from: {}", dir))?;
+        lsi::get_and_set_descriptions(self.storage, &mut entries)?;
+        self.filter.apply(&mut entries);
+        entries.sort();
+
+        let index = selected.and_then(|s| entries.iter().position(|e| e.as_path() == s));
+        self.state.select(match entries.is_empty() {
+            true => None,
+            false => Some(index.unwrap_or(0)),
+        });
+        self.entries = entries;
+        Ok(())
+    }
+
+    /// Gets the selected entry.
+    fn selected(&self) -> Option<&LsiPath> {
+        self.state.selected().and_then(|i| self.entries.get(i))
+    }
+
+    /// Handles key presses until the user quits.
+    ///
+    /// # Returns
+    ///
+    /// The directory to print, or `None` if the browser was cancelled.
+    fn event_loop(&mut self, terminal: &mut Terminal<CrosstermBackend<Stderr>>) -> Result<Option<PathBuf>> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            if self.editor.is_some() {
+                self.edit_key(key);
+                continue;
+            }
+            let is_ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+            match key.code {
+                KeyCode::Char('q') => return Ok(Some(self.dir.clone())),
+                KeyCode::Esc => return Ok(None),
+                KeyCode::Char('c') if is_ctrl => return Ok(None),
+                KeyCode::Up | KeyCode::Char('k') => self.state.select_previous(),
+                KeyCode::Down | KeyCode::Char('j') => self.state.select_next(),
+                KeyCode::Home => self.state.select_first(),
+                KeyCode::End => self.state.select_last(),
+                KeyCode::Right | KeyCode::Enter | KeyCode::Char('l') => self.enter(),
+                KeyCode::Left | KeyCode::Backspace | KeyCode::Char('h') => self.leave(),
+                KeyCode::Char('e') => self.start_edit(),
+                _ => {}
+            }
+        }
+    }
+
+    /// Moves into the selected directory.
+    fn enter(&mut self) {
+        let Some(path) = self.selected().map(|e| e.as_path().to_path_buf()) else {
+            return;
+        };
+        if !path.is_dir() {
+            return;
+        }
+        let previous = std::mem::replace(&mut self.dir, path);
+        if let Err(e) = self.load(None) {
+            self.message = Some(format!("{:#}", e));
+            self.dir = previous;
+            let _ = self.load(None);
+        }
+    }
+
+    /// Moves to the parent directory, selecting the directory we came from.
+    fn leave(&mut self) {
+        let Some(parent) = self.dir.parent().map(Path::to_path_buf) else {
+            return;
+        };
+        let previous = std::mem::replace(&mut self.dir, parent);
+        if let Err(e) = self.load(Some(&previous)) {
+            self.message = Some(format!("{:#}", e));
+        }
+    }
+
+    /// Opens the description of the selected entry in the inline editor.
+    fn start_edit(&mut self) {
+        let Some(path) = self.selected() else {
+            return;
+        };
+        let content = match fs::read_current_description(self.storage, path.as_path()) {
+            Ok(content) => content,
+            Err(e) => {
+                self.message = Some(format!("{:#}", e));
+                return;
+            }
+        };
+        let mut editor = TextArea::from(content.lines().map(str::to_string));
+        editor.set_block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(" Edit {} ", path.file_name())),
+        );
+        self.editor = Some(editor);
+        self.message = None;
+    }
+
+    /// Handles a key press in the inline editor.
+    fn edit_key(&mut self, key: KeyEvent) {
+        let Some(editor) = self.editor.as_mut() else {
+            return;
+        };
+        let is_ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => self.editor = None,
+            KeyCode::Char('s') if is_ctrl => {
+                let content = editor.lines().join("\n");
+                self.editor = None;
+                self.save(content);
+            }
+            _ => {
+                editor.input(key);
+            }
+        }
+    }
+
+    /// Writes the edited description of the selected entry, removing it if `content` is empty.
+    fn save(&mut self, content: String) {
+        let Some(path) = self.selected().map(|e| e.as_path().to_path_buf()) else {
+            return;
+        };
+        let result = match content.trim().is_empty() {
+            true => self
+                .storage
+                .delete(&path)
+                .map(|_| format!("Success: Remove description of {}", path.display())),
+            false => fs::store_description(self.storage, &path, &content)
+                .map(|location| format!("Success: Write description to {}", location)),
+        };
+        self.message = Some(match result {
+            Ok(message) => message,
+            Err(e) => format!("{:#}", e),
+        });
+        if let Err(e) = self.load(Some(&path)) {
+            self.message = Some(format!("{:#}", e));
+        }
+    }
+
+    /// Draws the listing, the preview (or editor) and the status line.
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, status] = Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+        let [list_area, preview_area] =
+            Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(main);
+
+        let items: Vec<ListItem> = self.entries.iter().map(list_item).collect();
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(format!(" {} ", self.dir.display())))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, list_area, &mut self.state);
+
+        match &self.editor {
+            Some(editor) => frame.render_widget(editor, preview_area),
+            None => {
+                let preview = Paragraph::new(self.selected().map(preview).unwrap_or_default())
+                    .block(Block::default().borders(Borders::ALL).title(" Description "))
+                    .wrap(Wrap { trim: false });
+                frame.render_widget(preview, preview_area);
+            }
+        }
+
+        let help = match self.editor {
+            Some(_) => EDIT_HELP,
+            None => BROWSE_HELP,
+        };
+        let line = match &self.message {
+            Some(message) => Line::from(message.as_str()),
+            None => Line::from(help).style(Style::default().fg(Color::DarkGray)),
+        };
+        frame.render_widget(Paragraph::new(line), status);
+    }
+}
+
+/// Formats an entry of the listing: its name and the first line of its description.
+fn list_item(path: &LsiPath) -> ListItem<'static> {
+    let name = match path.as_path().is_dir() {
+        true => Span::styled(
+            format!("{}/", path.file_name()),
+            Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+        ),
+        false => Span::raw(path.file_name().to_string()),
+    };
+    let mut spans = vec![name];
+    if let Some(first) = path.get_plain_description().and_then(|d| d.lines().next().map(str::to_string)) {
+        spans.push(Span::raw("  "));
+        spans.push(Span::styled(first, Style::default().fg(Color::Yellow)));
+    }
+    ListItem::new(Line::from(spans))
+}
+
+/// Formats the preview of an entry: its description fields followed by the description.
+fn preview(path: &LsiPath) -> Vec<Line<'static>> {
+    let mut lines: Vec<Line> = path
+        .get_fields()
+        .iter()
+        .map(|(key, value)| {
+            Line::from(vec![
+                Span::styled(format!("{}: ", key), Style::default().fg(Color::Green)),
+                Span::raw(value.clone()),
+            ])
+        })
+        .collect();
+    if !lines.is_empty() {
+        lines.push(Line::default());
+    }
+    match path.get_plain_description() {
+        Some(description) => lines.extend(description.lines().map(|l| Line::from(l.to_string()))),
+        None if lines.is_empty() => {
+            lines.push(Line::from("(no description)").style(Style::default().fg(Color::DarkGray)))
+        }
+        None => {}
+    }
+    lines
+}
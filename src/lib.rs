@@ -5,23 +5,57 @@
 //! This library provides the core functionality for the lsi command-line tool.
 
 pub mod colors;
+pub mod completions;
 pub mod config;
 pub mod decoration;
 pub mod errors;
+pub mod filter;
 pub mod fs;
+pub mod git;
+pub mod history;
+pub mod hook;
+pub mod interactive;
 pub mod lsi;
+pub mod metadata;
+pub mod migrate;
 pub mod mkdiri;
 pub mod path;
+pub mod search;
+pub mod storage;
+pub mod tag;
+pub mod template;
+pub mod transfer;
 pub mod view;
+pub mod watch;
 
+#[cfg(test)]
+mod testing;
+
+use fs::DescriptionEdit;
 use path::LsiPathKind;
+use view::Layout;
 
 /// This struct encapsulates command-line argument values and configurations for lsi/mkdiri.
+#[derive(Default)]
 pub struct LsiArgs<'a> {
     /// The path to list or manage.
     pub path: &'a str,
+    /// All paths given on the command line (the first one is `path`).
+    pub paths: Vec<&'a str>,
     /// Whether to show hidden files or not.
     pub show_hidden: bool,
+    /// Whether to print the given paths themselves in a flat list instead of listing directories.
+    pub flat: bool,
+    /// Whether to show the git status of each entry.
+    pub show_git: bool,
+    /// Whether to browse the listing interactively.
+    pub interactive: bool,
+    /// Whether to redraw the listing whenever it changes.
+    pub watch: bool,
+    /// Whether to show only the description of `path` and its described entries.
+    pub compact: bool,
+    /// The listing layout chosen on the command line, overriding the config file.
+    pub layout: Option<Layout>,
     /// An optional filter to list only files or directories.
     pub is_only: Option<LsiPathKind>,
     /// The path to the configuration file.
@@ -32,8 +66,42 @@ pub struct LsiArgs<'a> {
     pub is_mkdiri_mode: bool,
     /// The description to set (if applicable).
     pub set_description: Option<&'a str>,
-    /// The description to edit (if applicable).
+    /// The editor to edit the description with, overriding `$VISUAL`/`$EDITOR` (if applicable).
     pub edit_description: Option<&'a str>,
-    /// The mode for sorting entries.
+    /// Whether to open the description in an editor.
+    pub open_editor: bool,
+    /// The name of the configured template to write (if applicable).
+    pub template: Option<&'a str>,
+    /// How the given description is combined with the existing one.
+    pub description_edit: DescriptionEdit,
+    /// Whether `mkdiri`/`touchi` create missing parent directories.
+    pub parents: bool,
+    /// Whether to remove the descriptions of the given paths.
+    pub remove_description: bool,
+    /// Whether to read `path<TAB>description` lines from stdin.
+    pub from_stdin: bool,
+    /// The mode for sorting entries (`p`, `d`, or `k:<key>[:desc]` for a field).
     pub sort_mode: String,
+    /// The front-matter fields to show as columns.
+    pub columns: Vec<&'a str>,
+    /// Field predicates (`key<op>value`) that listed paths must satisfy.
+    pub where_clauses: Vec<&'a str>,
+    /// Whether to list only paths with (`Some(true)`) or without (`Some(false)`) a description.
+    pub has_description: Option<bool>,
+    /// A regex that the description of listed paths must match, or the `lsi search` pattern.
+    pub desc_match: Option<&'a str>,
+    /// Whether `lsi search` matches case-insensitively.
+    pub ignore_case: bool,
+    /// Tags to add or remove with `lsi tag`, or that listed paths must carry.
+    pub tags: Vec<&'a str>,
+    /// Whether `lsi tag` removes the tags instead of adding them.
+    pub remove_tags: bool,
+    /// The recorded change to restore, 1 being the latest (if applicable).
+    pub history_index: Option<usize>,
+    /// The storage backend to migrate descriptions to (if applicable).
+    pub migrate_to: Option<&'a str>,
+    /// The file to export descriptions to or import them from (if applicable).
+    pub transfer_file: Option<&'a str>,
+    /// The format of the export/import file (if applicable).
+    pub transfer_format: Option<&'a str>,
 }
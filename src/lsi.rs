@@ -3,19 +3,29 @@
 //! fetching descriptions, and displaying the results in the desired format.
 
 use crate::colors::Colors;
-use crate::config::read_config;
+use crate::config::{read_config, ViewConf};
 use crate::errors::LsiError;
+use crate::filter::Filter;
 use crate::path::LsiPath;
-use crate::{fs, view, LsiArgs};
+use crate::storage::{self, DescriptionStorage};
+use crate::{fs, git, interactive, metadata, view, LsiArgs};
+use crate::view::Layout;
 use anyhow::{Context, Result};
+use std::path::Path;
+
+/// The number of lines `--compact` prints unless `max_lines` is configured.
+const DEFAULT_COMPACT_LINES: usize = 10;
 
 /// Executes the main functionality of the `lsi` application.
 ///
 /// This function:
-/// - Globs target files and directories based on provided arguments
 /// - Reads configuration settings
-/// - Sets file and directory descriptions
-/// - Displays the results in a structured format
+/// - Lists the file arguments together, like `ls a b c` does
+/// - Lists each directory argument under its own header
+/// - Or, with `--paths`, prints the given paths themselves in a flat list
+/// - Or, with `-i`, opens the interactive browser on the first path
+/// - Or, with `--compact`, prints the description of the first path and its described entries
+/// - Sets file and directory descriptions and displays the results in a structured format
 ///
 /// # Parameters
 ///
@@ -27,47 +37,220 @@ use anyhow::{Context, Result};
 ///
 /// # Errors
 ///
-/// - `LsiError::PathNotFound`: If the specified path cannot be found
+/// - `LsiError::PathNotFound`: If one of the specified paths cannot be found (the others are still listed)
 /// - `LsiError::FailedDisplay`: If the display function encounters an error
+/// - `LsiError::InvalidFilter`: If a `--where` predicate or `--desc-match` regex is malformed
 pub fn run(args: &LsiArgs) -> Result<()> {
-    // Glob target files and directories
-    let mut paths = fs::get_pathes(&args.path, &args.is_only, &args.show_hidden, &args.sort_mode)
-        .with_context(|| format!("Failed to get paths from: {}", args.path))?;
-
     // Read Configs
     let config = args.config_path.and_then(|path| read_config(path.to_string()));
     let colors_config = config.as_ref().and_then(|c| c.colors.as_ref());
     let colors = Colors::new(colors_config);
+    let storage = storage::open(config.as_ref().and_then(|c| c.storage.as_ref()))?;
+    let filter = Filter::new(args)?;
+
+    if args.interactive {
+        return interactive::run(storage.as_ref(), &filter, args);
+    }
+    if args.compact {
+        let max_lines = config
+            .as_ref()
+            .and_then(|c| c.compact.as_ref())
+            .and_then(|c| c.max_lines)
+            .unwrap_or(DEFAULT_COMPACT_LINES);
+        return run_compact(storage.as_ref(), &filter, &colors, args, max_lines);
+    }
+    if args.flat {
+        return run_flat(storage.as_ref(), &filter, &colors, args);
+    }
+
+    // The layout given on the command line wins over the config file.
+    let layout = match (args.layout, config.as_ref().and_then(|c| c.view.as_ref())) {
+        (Some(layout), _) => layout,
+        (None, Some(ViewConf { layout: Some(name) })) => Layout::parse(name)?,
+        _ => Layout::Tree,
+    };
+
+    let mut missing = Vec::new();
+    let mut files = Vec::new();
+    let mut dirs = Vec::new();
+    for &path in &args.paths {
+        match Path::new(path) {
+            p if p.is_dir() => dirs.push(path),
+            p if p.exists() => files.push(LsiPath::new(p.to_path_buf(), &args.sort_mode)),
+            _ => missing.push(path),
+        }
+    }
+
+    let mut is_first = true;
+    if !files.is_empty() {
+        display_paths(storage.as_ref(), &filter, &colors, args, layout, files, None)?;
+        is_first = false;
+    }
+    for dir in dirs {
+        if !is_first {
+            println!();
+        }
+        // Glob target files and directories
+        let paths = fs::get_pathes(dir, &args.is_only, &args.show_hidden, &args.sort_mode)
+            .with_context(|| format!("Failed to get paths from: {}", dir))?;
+        display_paths(storage.as_ref(), &filter, &colors, args, layout, paths, Some(dir))?;
+        is_first = false;
+    }
+
+    if !missing.is_empty() {
+        return Err(LsiError::PathNotFound)
+            .with_context(|| format!("Failed to get paths from: {}", missing.join(", ")));
+    }
+    Ok(())
+}
+
+/// Prints every given path with its description in a flat list, in the given order.
+///
+/// # Parameters
+///
+/// - `storage`: The storage backend holding the descriptions
+/// - `filter`: The filters selecting the paths to display
+/// - `colors`: The display colors
+/// - `args`: A reference to `LsiArgs` containing the paths and display options
+///
+/// # Errors
+///
+/// - `LsiError::PathNotFound`: If one of the specified paths cannot be found (the others are still printed)
+fn run_flat(storage: &dyn DescriptionStorage, filter: &Filter, colors: &Colors, args: &LsiArgs) -> Result<()> {
+    let (existing, missing): (Vec<&str>, Vec<&str>) =
+        args.paths.iter().partition(|p| Path::new(p).exists());
+    let mut paths: Vec<LsiPath> = existing
+        .iter()
+        .map(|p| LsiPath::new(Path::new(p).to_path_buf(), &args.sort_mode))
+        .collect();
+
+    get_and_set_descriptions(storage, &mut paths)
+        .with_context(|| "Failed to retrieve descriptions for paths")?;
+    if args.show_git {
+        git::set_statuses(&mut paths);
+    }
+    filter.apply(&mut paths);
+    view::display_flat(&paths, colors, &args.columns)
+        .map_err(|e| LsiError::FailedDisplay(e.to_string()))?;
 
+    if !missing.is_empty() {
+        return Err(LsiError::PathNotFound)
+            .with_context(|| format!("Failed to get paths from: {}", missing.join(", ")));
+    }
+    Ok(())
+}
+
+/// Prints the description of `args.path` and its described entries, one line each.
+///
+/// Nothing is printed when neither the directory nor any entry has a description, so the
+/// `lsi init` hook stays quiet in undescribed directories.
+///
+/// # Parameters
+///
+/// - `storage`: The storage backend holding the descriptions
+/// - `filter`: The filters selecting the entries to display
+/// - `colors`: The display colors
+/// - `args`: A reference to `LsiArgs` containing the path and display options
+/// - `max_lines`: The maximum number of lines to print, or `0` for no limit
+///
+/// # Errors
+///
+/// - `LsiError::PathNotFound`: If the path cannot be read
+fn run_compact(
+    storage: &dyn DescriptionStorage,
+    filter: &Filter,
+    colors: &Colors,
+    args: &LsiArgs,
+    max_lines: usize,
+) -> Result<()> {
+    let mut dir = LsiPath::new(Path::new(args.path).to_path_buf(), &args.sort_mode);
+    let mut paths = match dir.as_path().is_dir() {
+        true => fs::get_pathes(args.path, &args.is_only, &args.show_hidden, &args.sort_mode)
+            .with_context(|| format!("Failed to get paths from: {}", args.path))?,
+        false => {
+            return Err(LsiError::PathNotFound)
+                .with_context(|| format!("Failed to get paths from: {}", args.path))
+        }
+    };
+    get_and_set_description(storage, &mut dir)?;
+    get_and_set_descriptions(storage, &mut paths)
+        .with_context(|| "Failed to retrieve descriptions for paths")?;
+    filter.apply(&mut paths);
+    paths.retain(|p| p.get_description().is_some() || !p.get_fields().is_empty());
+    paths.sort();
+
+    view::display_compact(dir.get_plain_description().as_deref(), &paths, colors, max_lines)
+        .map_err(|e| LsiError::FailedDisplay(e.to_string()))?;
+    Ok(())
+}
+
+/// Reads the descriptions of `paths`, filters and displays them.
+///
+/// # Parameters
+///
+/// - `storage`: The storage backend holding the descriptions
+/// - `filter`: The filters selecting the paths to display
+/// - `colors`: The display colors
+/// - `args`: A reference to `LsiArgs` containing the display options
+/// - `layout`: How the paths are laid out
+/// - `paths`: The paths to display
+/// - `cwd`: The listed directory, shown as a header, or `None` for file arguments
+///   (the `oneline` and `grid` layouts only show it when several paths are listed)
+///
+/// # Returns
+///
+/// - `Result<()>`: Success returns `Ok(())`, while any errors return a relevant `anyhow::Error`
+fn display_paths(
+    storage: &dyn DescriptionStorage,
+    filter: &Filter,
+    colors: &Colors,
+    args: &LsiArgs,
+    layout: Layout,
+    mut paths: Vec<LsiPath>,
+    cwd: Option<&str>,
+) -> Result<()> {
     // Read and set descriptions
-    get_and_set_descriptions(&mut paths)
+    get_and_set_descriptions(storage, &mut paths)
         .with_context(|| "Failed to retrieve descriptions for paths")?;
+    if args.show_git {
+        git::set_statuses(&mut paths);
+    }
+
+    // Keep only the paths matching the filters
+    filter.apply(&mut paths);
 
     // Display LSI results
-    view::display(&mut paths, &colors, args.path, &args.desc_num)
-        .map_err(|e| LsiError::FailedDisplay(e.to_string()))?;
-    
+    let header = cwd.filter(|_| args.paths.len() > 1);
+    match layout {
+        Layout::Tree => view::display(&mut paths, colors, cwd, &args.desc_num, &args.columns),
+        Layout::Oneline => view::display_oneline(&mut paths, colors, header, &args.columns),
+        Layout::Grid => view::display_grid(&mut paths, colors, header),
+    }
+    .map_err(|e| LsiError::FailedDisplay(e.to_string()))?;
     Ok(())
 }
 
 /// Retrieves and sets the description for a given file or directory path.
 ///
+/// A front matter at the start of the description is split off into the path's
+/// key/value fields.
+///
 /// # Parameters
 ///
+/// - `storage`: The storage backend holding the descriptions
 /// - `path`: A mutable reference to an `LsiPath` object representing a file or directory
 ///
 /// # Returns
 ///
 /// - `Result<()>`: Success returns `Ok(())`, while any errors return a relevant `anyhow::Error`
-fn get_and_set_description(path: &mut LsiPath) -> Result<()> {
-    let description_result = match path.kind {
-        crate::path::LsiPathKind::Dir => fs::read_dir_description(path),
-        crate::path::LsiPathKind::File => fs::read_file_description(path),
-    };
-
-    match description_result {
+fn get_and_set_description(storage: &dyn DescriptionStorage, path: &mut LsiPath) -> Result<()> {
+    match fs::read_description(storage, path) {
         Ok(content) => {
-            path.set_description(content);
+            let (fields, body) = metadata::split_front_matter(&content);
+            path.set_fields(fields);
+            if !body.is_empty() {
+                path.set_description(body);
+            }
             Ok(())
         }
         Err(_) => {
@@ -82,14 +265,15 @@ fn get_and_set_description(path: &mut LsiPath) -> Result<()> {
 ///
 /// # Parameters
 ///
+/// - `storage`: The storage backend holding the descriptions
 /// - `paths`: A mutable reference to a vector of `LsiPath` objects
 ///
 /// # Returns
 ///
 /// - `Result<()>`: Success returns `Ok(())`, while any errors return a relevant `anyhow::Error`
-fn get_and_set_descriptions(paths: &mut [LsiPath]) -> Result<()> {
+pub fn get_and_set_descriptions(storage: &dyn DescriptionStorage, paths: &mut [LsiPath]) -> Result<()> {
     for path in paths.iter_mut() {
-        let _ = get_and_set_description(path);
+        let _ = get_and_set_description(storage, path);
     }
     Ok(())
 }
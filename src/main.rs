@@ -10,85 +10,381 @@ extern crate clap;
 extern crate serde_derive;
 extern crate toml;
 
-use anyhow::Result;
-use async_std::io;
-use clap::App;
+use anyhow::{Context, Result};
+use clap::{App, Arg, ArgMatches};
+use ls_improved::fs::DescriptionEdit;
 use ls_improved::path::LsiPathKind;
-use ls_improved::{lsi, mkdiri, LsiArgs};
-use std::time::Duration;
+use ls_improved::view::Layout;
+use ls_improved::{completions, history, hook, lsi, migrate, mkdiri, search, tag, transfer, watch, LsiArgs};
+use std::io::{self, Read};
+use std::path::Path;
 
 /// The main function serves as the entry point of the application.
 ///
 /// It reads command-line arguments, configures the runtime environment,
-/// and dispatches tasks to relevant modules based on the specified options.
+/// and dispatches tasks to relevant modules based on the subcommand, or on
+/// the specified options when `lsi` is run without one.
 ///
 /// # Errors
 ///
 /// This function returns an `anyhow::Result` to handle various types of errors
 /// that might occur during IO operations or argument processing.
-#[async_std::main]
-async fn main() -> Result<()> {
-    // Load command-line arguments from the YAML file.
+fn main() -> Result<()> {
+    // Load command-line arguments from the YAML files.
     let yaml = load_yaml!("args.yml");
-    let args = App::from_yaml(yaml).get_matches();
-
-    // Retrieve values from the command-line arguments.
-    let path = args.value_of("PATH").unwrap_or(".");
-    let show_hidden = args.is_present("show_all");
-    let is_only_files = args.is_present("only_files");
-    let is_only_dirs = args.is_present("only_directories");
-    let config_path = args.value_of("config_path");
-
-    // Handle the optional description number.
-    let desc_num = match value_t!(args.value_of("desc_num"), usize) {
-        Ok(n) => Some(n),
-        Err(_) => None,
-    };
+    let ls_yaml = load_yaml!("ls.yml");
+    let mkdiri_yaml = load_yaml!("mkdiri.yml");
+    let touchi_yaml = load_yaml!("touchi.yml");
 
-    // Options for managing descriptions.
-    let set_description = args.value_of("set_description");
-    let edit_description = args.value_of("edit_description");
-    let is_edit_description = args.occurrences_of("edit_description") > 0;
-    let sort_mode = args.value_of("sort_mode").unwrap_or("p");
-
-    // Read piped input with a timeout.
-    let input = io::timeout(Duration::from_millis(1), async {
-        let stdin = io::stdin();
-        let mut line = String::new();
-        stdin.read_line(&mut line).await?;
-        Ok(line)
-    })
-    .await;
-
-    // Determine the effective path to use.
-    let path = match input {
-        Ok(mut i) => {
-            i.retain(|c| c != '\n');
-            i
+    // The listing options are shared by `lsi [PATH]...` and `lsi ls [PATH]...`.
+    let mut app = App::from_yaml(yaml);
+    for arg in ls_yaml["args"].as_vec().into_iter().flatten() {
+        if let Some(arg) = arg.as_hash() {
+            app = app.arg(Arg::from_yaml(arg));
+        }
+    }
+    let app = app
+        .subcommand(App::from_yaml(ls_yaml))
+        .subcommand(App::from_yaml(mkdiri_yaml))
+        .subcommand(App::from_yaml(touchi_yaml));
+    // Keep the definition around to generate completion scripts from.
+    let args = app.clone().get_matches();
+    if let Some(name) = args.subcommand_name() {
+        warn_shadowed_path(name);
+    }
+
+    match args.subcommand() {
+        ("completions", Some(sub)) => {
+            return completions::run(app, sub.value_of("SHELL").unwrap_or_default());
+        }
+        ("init", Some(sub)) => {
+            return hook::run_init(
+                &LsiArgs {
+                    config_path: sub.value_of("config_path"),
+                    ..Default::default()
+                },
+                sub.value_of("SHELL").unwrap_or_default(),
+            );
+        }
+        ("complete", Some(sub)) => {
+            return completions::run_candidates(
+                &LsiArgs {
+                    path: sub.value_of("PATH").unwrap_or("./"),
+                    config_path: sub.value_of("config_path"),
+                    ..Default::default()
+                },
+                sub.value_of("KIND").unwrap_or_default(),
+            );
+        }
+        ("ls", Some(sub)) => {
+            let stdin = read_stdin_paths(sub)?;
+            let args = listing_args(sub, stdin.as_deref());
+            return match args.watch {
+                true => watch::run(&args),
+                false => lsi::run(&args),
+            };
+        }
+        ("set", Some(sub)) => {
+            return mkdiri::run(&LsiArgs {
+                paths: sub.values_of("PATH").map(|v| v.collect()).unwrap_or_default(),
+                config_path: sub.value_of("config_path"),
+                is_mkdiri_mode: true,
+                set_description: sub.value_of("DESCRIPTION"),
+                description_edit: description_edit(sub),
+                from_stdin: sub.is_present("from_stdin"),
+                ..Default::default()
+            })
         }
-        Err(_) => path.to_string(),
+        ("edit", Some(sub)) => {
+            let path = sub.value_of("PATH").unwrap_or(".");
+            return mkdiri::run(&LsiArgs {
+                path,
+                paths: vec![path],
+                config_path: sub.value_of("config_path"),
+                is_mkdiri_mode: true,
+                edit_description: sub.value_of("editor"),
+                open_editor: true,
+                template: sub.value_of("template"),
+                ..Default::default()
+            });
+        }
+        ("rm", Some(sub)) => {
+            let line = match sub.is_present("line") {
+                true => Some(value_t_or_exit!(sub, "line", usize)),
+                false => None,
+            };
+            return mkdiri::run(&LsiArgs {
+                paths: sub.values_of("PATH").map(|v| v.collect()).unwrap_or_default(),
+                config_path: sub.value_of("config_path"),
+                is_mkdiri_mode: true,
+                description_edit: line.map_or(DescriptionEdit::Replace, DescriptionEdit::DeleteLine),
+                remove_description: line.is_none(),
+                ..Default::default()
+            });
+        }
+        ("search", Some(sub)) => {
+            return search::run(&LsiArgs {
+                path: sub.value_of("PATH").unwrap_or("."),
+                config_path: sub.value_of("config_path"),
+                desc_match: sub.value_of("PATTERN"),
+                ignore_case: sub.is_present("ignore_case"),
+                ..Default::default()
+            })
+        }
+        ("history", Some(sub)) => {
+            return history::run_history(&LsiArgs {
+                path: sub.value_of("PATH").unwrap_or("."),
+                config_path: sub.value_of("config_path"),
+                ..Default::default()
+            })
+        }
+        ("restore", Some(sub)) => {
+            let history_index = match sub.is_present("N") {
+                true => Some(value_t_or_exit!(sub, "N", usize)),
+                false => None,
+            };
+            return history::run_restore(&LsiArgs {
+                path: sub.value_of("PATH").unwrap_or("."),
+                config_path: sub.value_of("config_path"),
+                history_index,
+                ..Default::default()
+            });
+        }
+        ("export", Some(sub)) => {
+            return transfer::run_export(&LsiArgs {
+                path: sub.value_of("PATH").unwrap_or("."),
+                config_path: sub.value_of("config_path"),
+                transfer_file: sub.value_of("output"),
+                transfer_format: sub.value_of("format"),
+                ..Default::default()
+            })
+        }
+        ("import", Some(sub)) => {
+            return transfer::run_import(&LsiArgs {
+                path: sub.value_of("PATH").unwrap_or("."),
+                config_path: sub.value_of("config_path"),
+                transfer_file: sub.value_of("FILE"),
+                transfer_format: sub.value_of("format"),
+                ..Default::default()
+            })
+        }
+        ("mkdiri", Some(sub)) => return mkdiri::create(&mkdiri::create_args(sub, LsiPathKind::Dir), LsiPathKind::Dir),
+        ("touchi", Some(sub)) => return mkdiri::create(&mkdiri::create_args(sub, LsiPathKind::File), LsiPathKind::File),
+        ("tag", Some(sub)) => {
+            let paths: Vec<&str> = sub.values_of("PATH").map(|v| v.collect()).unwrap_or_default();
+            return tag::run(&LsiArgs {
+                path: paths[0],
+                paths,
+                config_path: sub.value_of("config_path"),
+                tags: sub.values_of("TAGS").map(|v| v.collect()).unwrap_or_default(),
+                remove_tags: sub.is_present("remove"),
+                ..Default::default()
+            });
+        }
+        _ => {}
+    }
+
+    // Without a subcommand, the options select between listing and managing descriptions.
+    let set_description = args.value_of("set_description");
+    let is_edit_description = args.is_present("edit_description");
+    let template = args.value_of("template");
+    let description_edit = description_edit(&args);
+    let from_stdin = args.is_present("from_stdin");
+    let remove_description = args.is_present("remove_description");
+
+    // With `--from-stdin`, stdin carries descriptions rather than paths.
+    let stdin = match from_stdin {
+        true => None,
+        false => read_stdin_paths(&args)?,
     };
 
     // Configuration arguments for lsi/mkdiri.
     let args = LsiArgs {
-        path: &path,
-        show_hidden,
-        is_only: match (is_only_files, is_only_dirs) {
+        is_mkdiri_mode: set_description.is_some()
+            || is_edit_description
+            || template.is_some()
+            || from_stdin
+            || remove_description
+            || matches!(description_edit, DescriptionEdit::DeleteLine(_)),
+        set_description,
+        edit_description: args.value_of("editor"),
+        open_editor: is_edit_description,
+        template,
+        description_edit,
+        remove_description,
+        from_stdin,
+        migrate_to: args.value_of("migrate_to"),
+        ..listing_args(&args, stdin.as_deref())
+    };
+
+    // Execute the appropriate module based on the mode.
+    if args.migrate_to.is_some() {
+        return migrate::run(&args);
+    }
+    match (args.is_mkdiri_mode, args.watch) {
+        (true, _) => mkdiri::run(&args),
+        (false, true) => watch::run(&args),
+        (false, false) => lsi::run(&args),
+    }
+}
+
+/// Tells on stderr that the subcommand `name` is run even though a path of that name
+/// exists, since `lsi [PATH]` would otherwise seem to ignore it.
+///
+/// Nothing is said for `ls`, whose arguments are listed anyway, or when the subcommand is
+/// given arguments, which makes the intent clear.
+fn warn_shadowed_path(name: &str) {
+    let words: Vec<String> = std::env::args().collect();
+    let is_bare = words.iter().position(|arg| arg == name) == Some(words.len() - 1);
+    if name != "ls" && is_bare && Path::new(name).exists() {
+        eprintln!(
+            "Note: `{}` is run as a subcommand; use `lsi ls {}` or `lsi ./{}` to list the path",
+            name, name, name
+        );
+    }
+}
+
+/// Builds the listing arguments shared by `lsi [PATH]...` and `lsi ls [PATH]...`.
+///
+/// # Arguments
+///
+/// * `args` - The matches holding the listing options.
+/// * `stdin` - The paths read from stdin with `--stdin` or `-`, if any.
+///
+/// # Returns
+///
+/// The `LsiArgs` for listing.
+fn listing_args<'a>(args: &'a ArgMatches<'a>, stdin: Option<&'a str>) -> LsiArgs<'a> {
+    // `PATH` defaults to `./`, which only applies when no path comes from stdin either.
+    let given: Vec<&str> = match args.occurrences_of("PATH") {
+        0 => Vec::new(),
+        _ => args.values_of("PATH").map(|v| v.collect()).unwrap_or_default(),
+    };
+    let stdin_paths = stdin.map(split_paths).unwrap_or_default();
+    let mut paths = Vec::new();
+    for path in &given {
+        match *path {
+            "-" => paths.extend(&stdin_paths),
+            p => paths.push(p),
+        }
+    }
+    if !given.contains(&"-") {
+        paths.extend(&stdin_paths);
+    }
+    if paths.is_empty() && stdin.is_none() {
+        paths.push(".");
+    }
+    let sort_mode = match args.value_of("sort_by") {
+        Some(key) => format!("k:{}", key),
+        None => args.value_of("sort_mode").unwrap_or("p").to_string(),
+    };
+
+    LsiArgs {
+        path: paths.first().copied().unwrap_or("."),
+        paths,
+        flat: args.is_present("flat"),
+        show_git: args.is_present("git"),
+        interactive: args.is_present("interactive"),
+        watch: args.is_present("watch"),
+        compact: args.is_present("compact"),
+        layout: match (args.is_present("oneline"), args.is_present("grid")) {
+            (true, _) => Some(Layout::Oneline),
+            (_, true) => Some(Layout::Grid),
+            _ => None,
+        },
+        show_hidden: args.is_present("show_all"),
+        is_only: match (args.is_present("only_files"), args.is_present("only_directories")) {
             (true, _) => Some(LsiPathKind::File),
             (_, true) => Some(LsiPathKind::Dir),
             _ => None,
         },
-        config_path,
-        desc_num,
-        is_mkdiri_mode: set_description.is_some() || is_edit_description,
-        set_description,
-        edit_description,
-        sort_mode: sort_mode.to_string(),
-    };
+        config_path: args.value_of("config_path"),
+        desc_num: value_t!(args.value_of("desc_num"), usize).ok(),
+        sort_mode,
+        columns: args.values_of("columns").map(|v| v.collect()).unwrap_or_default(),
+        where_clauses: args.values_of("where").map(|v| v.collect()).unwrap_or_default(),
+        has_description: match (args.is_present("has_description"), args.is_present("no_description")) {
+            (true, _) => Some(true),
+            (_, true) => Some(false),
+            _ => None,
+        },
+        desc_match: args.value_of("desc_match"),
+        tags: args.values_of("tag").map(|v| v.collect()).unwrap_or_default(),
+        ..Default::default()
+    }
+}
 
-    // Execute the appropriate module based on the mode.
-    match args.is_mkdiri_mode {
-        true => mkdiri::run(&args),
-        false => lsi::run(&args),
+/// Reads how the given description is combined with the existing one.
+///
+/// # Arguments
+///
+/// * `args` - The matches holding `--append`, `--prepend`, `--set-line` or `--delete-line`.
+///
+/// # Returns
+///
+/// The selected `DescriptionEdit`, `Replace` by default.
+fn description_edit(args: &ArgMatches) -> DescriptionEdit {
+    if args.is_present("append") {
+        DescriptionEdit::Append
+    } else if args.is_present("prepend") {
+        DescriptionEdit::Prepend
+    } else if args.is_present("set_line") {
+        DescriptionEdit::SetLine(value_t_or_exit!(args, "set_line", usize))
+    } else if args.is_present("delete_line") {
+        DescriptionEdit::DeleteLine(value_t_or_exit!(args, "delete_line", usize))
+    } else {
+        DescriptionEdit::Replace
+    }
+}
+
+/// Reads all of stdin if paths are requested from it with `--stdin` or a `-` argument.
+///
+/// # Arguments
+///
+/// * `args` - The matches holding the listing options.
+///
+/// # Errors
+///
+/// Returns an error if stdin cannot be read.
+///
+/// # Returns
+///
+/// The content of stdin, or `None` if paths are not read from stdin.
+fn read_stdin_paths(args: &ArgMatches) -> Result<Option<String>> {
+    let is_requested = args.is_present("stdin")
+        || (args.occurrences_of("PATH") > 0
+            && args.values_of("PATH").is_some_and(|mut v| v.any(|p| p == "-")));
+    if !is_requested {
+        return Ok(None);
+    }
+    let mut input = String::new();
+    io::stdin()
+        .read_to_string(&mut input)
+        .context("Failed to read paths from stdin")?;
+    Ok(Some(input))
+}
+
+/// Splits the paths read from stdin on NUL bytes (as written by `find -print0`)
+/// if there are any, and on newlines otherwise. Empty entries are skipped.
+fn split_paths(input: &str) -> Vec<&str> {
+    match input.contains('\0') {
+        true => input.split('\0').filter(|p| !p.is_empty()).collect(),
+        false => input.lines().filter(|p| !p.is_empty()).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_paths_on_newlines() {
+        assert_eq!(split_paths("a\nb c\n\nd\r\n"), ["a", "b c", "d"]);
+        assert!(split_paths("").is_empty());
+    }
+
+    #[test]
+    fn split_paths_on_nul_bytes() {
+        assert_eq!(split_paths("a\0b\nc\0\0d\0"), ["a", "b\nc", "d"]);
     }
 }
@@ -0,0 +1,256 @@
+//! This module parses the optional key/value header of a description.
+//!
+//! A description may start with a TOML front matter delimited by `+++` lines;
+//! the remaining text is the free-text description:
+//!
+//! ```text
+//! +++
+//! lr = 0.001
+//! acc = 0.87
+//! +++
+//! Baseline with warmup
+//! ```
+
+use anyhow::{Context, Result};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use toml::Value;
+
+/// Line delimiting the front matter.
+pub const FRONT_MATTER_DELIMITER: &str = "+++";
+
+/// Front-matter field holding the tags of a path.
+pub const TAGS_FIELD: &str = "tags";
+
+/// Splits a description into its front-matter fields and its free text.
+///
+/// Descriptions without a (valid) front matter are returned unchanged with no fields.
+///
+/// # Arguments
+///
+/// * `content` - The raw description.
+///
+/// # Returns
+///
+/// A tuple of the fields, rendered as display strings, and the free text.
+pub fn split_front_matter(content: &str) -> (BTreeMap<String, String>, String) {
+    match parse_front_matter(content) {
+        Some((table, body)) => {
+            let fields = table
+                .into_iter()
+                .map(|(key, value)| (key, render_value(&value)))
+                .collect();
+            (fields, body)
+        }
+        None => (BTreeMap::new(), content.to_string()),
+    }
+}
+
+/// Parses the front matter of a description into a TOML table and the free text.
+///
+/// # Returns
+///
+/// `None` if the description has no (valid) front matter.
+fn parse_front_matter(content: &str) -> Option<(toml::Table, String)> {
+    let mut lines = content.lines();
+    if lines.next().map(str::trim) != Some(FRONT_MATTER_DELIMITER) {
+        return None;
+    }
+
+    let mut header = Vec::new();
+    let mut closed = false;
+    for line in lines.by_ref() {
+        if line.trim() == FRONT_MATTER_DELIMITER {
+            closed = true;
+            break;
+        }
+        header.push(line);
+    }
+    if !closed {
+        return None;
+    }
+
+    let table = toml::from_str(&header.join("\n")).ok()?;
+    let body = lines.collect::<Vec<_>>().join("\n").trim().to_string();
+    Some((table, body))
+}
+
+/// Parses tags written as `#baseline #paper`, `baseline,paper` or a mix of both.
+///
+/// # Arguments
+///
+/// * `text` - The tags separated by whitespace or commas, each with an optional `#`.
+///
+/// # Returns
+///
+/// The tag names without `#`, in order and without duplicates.
+pub fn parse_tags(text: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in text.split(|c: char| c.is_whitespace() || c == ',') {
+        let tag = tag.trim_start_matches('#');
+        if !tag.is_empty() && !tags.iter().any(|t| t == tag) {
+            tags.push(tag.to_string());
+        }
+    }
+    tags
+}
+
+/// Reads the tags stored in the front matter of a description.
+///
+/// # Arguments
+///
+/// * `content` - The raw description.
+///
+/// # Returns
+///
+/// The tags, or an empty vector if there are none.
+pub fn get_tags(content: &str) -> Vec<String> {
+    parse_front_matter(content)
+        .and_then(|(table, _)| table.get(TAGS_FIELD).map(render_value))
+        .map(|tags| parse_tags(&tags))
+        .unwrap_or_default()
+}
+
+/// Replaces the tags in the front matter of a description, keeping the other fields and the free text.
+///
+/// The front matter is dropped altogether once it holds no field.
+///
+/// # Arguments
+///
+/// * `content` - The raw description.
+/// * `tags` - The new tags.
+///
+/// # Errors
+///
+/// Returns an error if the front matter cannot be serialized.
+///
+/// # Returns
+///
+/// The updated description.
+pub fn with_tags(content: &str, tags: &[String]) -> Result<String> {
+    let (mut table, body) =
+        parse_front_matter(content).unwrap_or_else(|| (toml::Table::new(), content.trim().to_string()));
+    match tags.is_empty() {
+        true => table.remove(TAGS_FIELD),
+        false => table.insert(
+            TAGS_FIELD.to_string(),
+            Value::Array(tags.iter().cloned().map(Value::String).collect()),
+        ),
+    };
+    if table.is_empty() {
+        return Ok(body);
+    }
+    let header = toml::to_string(&table).context("Failed to serialize front matter")?;
+    Ok(format!(
+        "{}\n{}{}\n{}",
+        FRONT_MATTER_DELIMITER, header, FRONT_MATTER_DELIMITER, body
+    ))
+}
+
+/// Renders a TOML value for display: strings without quotes, arrays comma-separated.
+fn render_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Array(a) => a.iter().map(render_value).collect::<Vec<_>>().join(", "),
+        other => other.to_string(),
+    }
+}
+
+/// Compares two field values, numerically if both are numbers.
+///
+/// # Arguments
+///
+/// * `a` - The first value.
+/// * `b` - The second value.
+///
+/// # Returns
+///
+/// The ordering of the values; numbers sort before non-numeric text and `nan` sorts
+/// after every other number, so the ordering is total.
+pub fn compare_values(a: &str, b: &str) -> Ordering {
+    match (a.parse::<f64>(), b.parse::<f64>()) {
+        (Ok(x), Ok(y)) => x.is_nan().cmp(&y.is_nan()).then(x.total_cmp(&y)),
+        (Ok(_), Err(_)) => Ordering::Less,
+        (Err(_), Ok(_)) => Ordering::Greater,
+        (Err(_), Err(_)) => a.cmp(b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compare_values_orders_numbers_numerically() {
+        assert_eq!(compare_values("9", "10"), Ordering::Less);
+        assert_eq!(compare_values("1e-3", "0.01"), Ordering::Less);
+        assert_eq!(compare_values("10", "abc"), Ordering::Less);
+        assert_eq!(compare_values("abc", "abd"), Ordering::Less);
+    }
+
+    #[test]
+    fn compare_values_puts_nan_after_numbers() {
+        assert_eq!(compare_values("nan", "0.5"), Ordering::Greater);
+        assert_eq!(compare_values("0.5", "nan"), Ordering::Less);
+        assert_eq!(compare_values("nan", "nan"), Ordering::Equal);
+        assert_eq!(compare_values("nan", "text"), Ordering::Less);
+    }
+
+    #[test]
+    fn compare_values_is_a_total_order_with_nan() {
+        let mut values: Vec<String> = (0..40)
+            .map(|i| match i % 3 {
+                0 => "nan".to_string(),
+                _ => format!("0.{}", (i * 7) % 40),
+            })
+            .collect();
+        values.sort_by(|a, b| compare_values(a, b));
+        let first_nan = values.iter().position(|v| v == "nan").unwrap();
+        assert!(values[first_nan..].iter().all(|v| v == "nan"));
+        assert!(values[..first_nan]
+            .windows(2)
+            .all(|w| w[0].parse::<f64>().unwrap() <= w[1].parse::<f64>().unwrap()));
+    }
+
+    #[test]
+    fn split_front_matter_parses_fields_and_body() {
+        let (fields, body) = split_front_matter("+++\nlr = 0.001\nmodel = \"resnet\"\n+++\nBaseline\n");
+        assert_eq!(fields.get("lr").map(String::as_str), Some("0.001"));
+        assert_eq!(fields.get("model").map(String::as_str), Some("resnet"));
+        assert_eq!(body, "Baseline");
+    }
+
+    #[test]
+    fn split_front_matter_keeps_unclosed_or_invalid_headers_as_text() {
+        let unclosed = "+++\nlr = 0.001\nBaseline";
+        assert_eq!(split_front_matter(unclosed), (BTreeMap::new(), unclosed.to_string()));
+        let invalid = "+++\nnot toml\n+++\nBaseline";
+        assert_eq!(split_front_matter(invalid), (BTreeMap::new(), invalid.to_string()));
+        assert_eq!(split_front_matter("plain"), (BTreeMap::new(), "plain".to_string()));
+    }
+
+    #[test]
+    fn parse_tags_accepts_hashes_commas_and_duplicates() {
+        assert_eq!(parse_tags("#a, b #a  c"), vec!["a", "b", "c"]);
+        assert!(parse_tags(" , # ").is_empty());
+    }
+
+    #[test]
+    fn with_tags_adds_and_removes_the_tags_field() {
+        let tagged = with_tags("Baseline", &["paper".to_string()]).unwrap();
+        assert_eq!(get_tags(&tagged), vec!["paper"]);
+        assert_eq!(split_front_matter(&tagged).1, "Baseline");
+
+        let untagged = with_tags(&tagged, &[]).unwrap();
+        assert_eq!(untagged, "Baseline");
+    }
+
+    #[test]
+    fn with_tags_keeps_other_fields() {
+        let tagged = with_tags("+++\nlr = 0.1\n+++\nRun", &["x".to_string()]).unwrap();
+        let (fields, body) = split_front_matter(&tagged);
+        assert_eq!(fields.get("lr").map(String::as_str), Some("0.1"));
+        assert_eq!(fields.get("tags").map(String::as_str), Some("x"));
+        assert_eq!(body, "Run");
+    }
+}
@@ -0,0 +1,154 @@
+//! This module moves descriptions from one storage backend to another,
+//! e.g. from sidecar files to per-directory manifests and back.
+
+use crate::config::read_config;
+use crate::fs::walk_dirs;
+use crate::storage::{self, DescriptionStorage};
+use crate::LsiArgs;
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+/// Runs the migration based on the provided arguments.
+///
+/// Descriptions under `args.path` are moved from the configured backend to
+/// `args.migrate_to`.
+///
+/// # Arguments
+///
+/// * `args` - A reference to the `LsiArgs` struct containing the current configuration.
+///
+/// # Returns
+///
+/// Returns a `Result` which is `Ok` if the operation is successful or an error otherwise.
+pub fn run(args: &LsiArgs) -> Result<()> {
+    let config = args.config_path.and_then(|path| read_config(path.to_string()));
+    // Moving descriptions does not change them, so it is not recorded in the history.
+    let from = storage::open_backend(
+        config
+            .as_ref()
+            .and_then(|c| c.storage.as_ref())
+            .and_then(|s| s.backend.as_deref())
+            .unwrap_or("sidecar"),
+    )?;
+    let to = storage::open_backend(args.migrate_to.unwrap_or("sidecar"))?;
+
+    if from.name() == to.name() {
+        println!("Nothing to do: descriptions are already stored in {}", to.name());
+        return Ok(());
+    }
+
+    let count = migrate(from.as_ref(), to.as_ref(), &PathBuf::from(args.path))?;
+    println!(
+        "Success: Migrate {} descriptions from {} to {}",
+        count,
+        from.name(),
+        to.name()
+    );
+    Ok(())
+}
+
+/// Moves every description under `root` (including `root` itself) from `from` to `to`.
+///
+/// Each description is deleted from the old backend before it is written to the new one,
+/// since backends may share files (e.g. the xattr backend falls back to sidecars). If the
+/// write fails, the description is restored to the old backend.
+///
+/// # Arguments
+///
+/// * `from` - The backend currently holding the descriptions.
+/// * `to` - The backend to move the descriptions to.
+/// * `root` - The directory tree to migrate.
+///
+/// # Errors
+///
+/// Returns an error if a directory cannot be walked or a description cannot be moved.
+///
+/// # Returns
+///
+/// The number of migrated descriptions.
+pub fn migrate(from: &dyn DescriptionStorage, to: &dyn DescriptionStorage, root: &Path) -> Result<usize> {
+    let mut count = 0;
+    if let Ok(description) = from.read(root) {
+        move_description(from, to, root, &description)?;
+        count += 1;
+    }
+    for dir in walk_dirs(root)? {
+        for (path, description) in from.list(&dir)? {
+            move_description(from, to, &path, &description)?;
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+/// Moves a single description from `from` to `to`.
+fn move_description(
+    from: &dyn DescriptionStorage,
+    to: &dyn DescriptionStorage,
+    path: &Path,
+    description: &str,
+) -> Result<()> {
+    from.delete(path)
+        .with_context(|| format!("Failed to remove old description of {}", path.display()))?;
+    if let Err(e) = to.write(path, description) {
+        let _ = from.write(path, description);
+        return Err(e).with_context(|| format!("Failed to migrate description of {}", path.display()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::manifest::MANIFEST;
+    use crate::storage::sidecar::{DIR_DESCRIPTION, FILE_DESCRIPTION_DIR};
+    use crate::storage::{ManifestStorage, SidecarStorage};
+    use crate::testing::touch;
+    use std::fs;
+
+    /// Creates `runs/` with a description on itself, a file and a nested directory.
+    fn described_tree(storage: &dyn DescriptionStorage) -> (tempfile::TempDir, Vec<(PathBuf, &'static str)>) {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap().join("runs");
+        fs::create_dir_all(root.join("a")).unwrap();
+        let described = vec![
+            (root.clone(), "sweep"),
+            (touch(&root, "notes.txt"), "hyper parameters"),
+            (root.join("a"), "baseline"),
+            (touch(&root.join("a"), "log.txt"), "diverged"),
+        ];
+        for (path, description) in &described {
+            storage.write(path, description).unwrap();
+        }
+        (dir, described)
+    }
+
+    #[test]
+    fn migrate_from_sidecar_to_manifest() {
+        let (dir, described) = described_tree(&SidecarStorage);
+        let root = &described[0].0;
+
+        assert_eq!(migrate(&SidecarStorage, &ManifestStorage::new(), root).unwrap(), 4);
+        for (path, description) in &described {
+            assert_eq!(ManifestStorage::new().read(path).unwrap(), *description);
+            assert!(SidecarStorage.read(path).is_err());
+        }
+        assert!(!root.join(DIR_DESCRIPTION).exists());
+        assert!(!root.join(FILE_DESCRIPTION_DIR).exists());
+        assert!(!dir.path().join(MANIFEST).exists());
+    }
+
+    #[test]
+    fn migrate_from_manifest_to_sidecar() {
+        let (_dir, described) = described_tree(&ManifestStorage::new());
+        let root = &described[0].0;
+
+        assert_eq!(migrate(&ManifestStorage::new(), &SidecarStorage, root).unwrap(), 4);
+        for (path, description) in &described {
+            assert_eq!(SidecarStorage.read(path).unwrap(), *description);
+            assert!(ManifestStorage::new().read(path).is_err());
+        }
+        assert!(!root.join(MANIFEST).exists());
+        assert!(!root.join("a").join(MANIFEST).exists());
+    }
+}
@@ -1,12 +1,21 @@
 //! This module provides functionality for handling the mkdiri tasks.
 //! It includes functions to set or edit descriptions for directories or files,
-//! and to launch text editors.
+//! to create directories and files together with their descriptions
+//! (`mkdiri`/`touchi`), and to launch text editors.
 
+use crate::config::read_config;
 use crate::errors::LsiError;
-use crate::fs::write_description;
-use crate::LsiArgs;
-use anyhow::Result;
-use std::os::unix::process::CommandExt;
+use crate::fs::{
+    edit_description, read_current_description, remove_description, write_description, DescriptionEdit,
+};
+use crate::path::LsiPathKind;
+use crate::storage::{self, DescriptionStorage};
+use crate::{template, LsiArgs};
+use anyhow::{Context, Result};
+use clap::ArgMatches;
+use std::env;
+use std::fs::OpenOptions;
+use std::io::{self, BufRead, Write};
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
@@ -14,7 +23,10 @@ use std::process::Command;
 ///
 /// This function handles the main logic of the mkdiri module. It determines
 /// whether to set a new description, edit an existing one, or print an error
-/// if neither is provided.
+/// if neither is provided. A description can be set on several paths (or glob
+/// patterns) at once, or read per path from stdin with `--from-stdin`,
+/// descriptions can be removed with `--remove-description`, and a configured
+/// template can be written (or edited first) with `--template`.
 ///
 /// # Arguments
 ///
@@ -23,10 +35,279 @@ use std::process::Command;
 /// # Returns
 ///
 /// Returns a `Result` which is `Ok` if the operation is successful or an error otherwise.
-
 pub fn run(args: &LsiArgs) -> Result<()> {
-    let path = PathBuf::from(&args.path);
-    mkdiri_description(&path, args.set_description, args.edit_description)
+    let config = args.config_path.and_then(|path| read_config(path.to_string()));
+    let storage = storage::open(config.as_ref().and_then(|c| c.storage.as_ref()))?;
+
+    if args.from_stdin {
+        let entries = read_descriptions(io::stdin().lock())?;
+        return write_descriptions(storage.as_ref(), &entries, args.description_edit);
+    }
+
+    if args.remove_description {
+        let paths = expand_paths(&args.paths)?;
+        return match paths.as_slice() {
+            [path] => remove_description(storage.as_ref(), path),
+            _ => remove_descriptions(storage.as_ref(), &paths),
+        };
+    }
+
+    if let Some(name) = args.template {
+        let template = template::lookup(config.as_ref(), name)?;
+        let paths = expand_paths(&args.paths)?;
+        return match (paths.as_slice(), args.open_editor) {
+            ([path], true) => {
+                let seed = template::render(template, path);
+                launch_editor(storage.as_ref(), path, args.edit_description, Some(&seed))
+            }
+            (_, true) => Err(LsiError::FailedLaunchEditor("Only one PATH can be edited at a time".to_string()).into()),
+            (_, false) => {
+                let entries: Vec<(PathBuf, String)> = paths
+                    .into_iter()
+                    .map(|p| {
+                        let description = template::render(template, &p);
+                        (p, description)
+                    })
+                    .collect();
+                write_descriptions(storage.as_ref(), &entries, DescriptionEdit::Replace)
+            }
+        };
+    }
+
+    // Deleting a line needs no description text.
+    let description = match args.description_edit {
+        DescriptionEdit::DeleteLine(_) => Some(args.set_description.unwrap_or("")),
+        _ => args.set_description,
+    };
+
+    let paths = expand_paths(&args.paths)?;
+    match (paths.as_slice(), description) {
+        ([path], _) => mkdiri_description(
+            storage.as_ref(),
+            path,
+            description,
+            args.description_edit,
+            args.edit_description,
+        ),
+        (_, Some(d)) => {
+            let entries: Vec<(PathBuf, String)> =
+                paths.into_iter().map(|p| (p, d.to_string())).collect();
+            write_descriptions(storage.as_ref(), &entries, args.description_edit)
+        }
+        (_, None) => Err(LsiError::FailedLaunchEditor("Only one PATH can be edited at a time".to_string()).into()),
+    }
+}
+
+/// Builds the arguments of the `mkdiri` and `touchi` commands from their matches.
+///
+/// # Arguments
+///
+/// * `matches` - The matches of `mkdiri.yml` or `touchi.yml`.
+/// * `kind` - Whether a directory (`mkdiri`) or a file (`touchi`) is created.
+///
+/// # Returns
+///
+/// The `LsiArgs` for `create`.
+pub fn create_args<'a>(matches: &'a ArgMatches<'a>, kind: LsiPathKind) -> LsiArgs<'a> {
+    let target = match kind {
+        LsiPathKind::Dir => "DIR",
+        LsiPathKind::File => "FILE",
+    };
+    let path = matches.value_of(target).unwrap_or_default();
+    LsiArgs {
+        path,
+        paths: vec![path],
+        config_path: matches.value_of("config_path"),
+        set_description: matches.value_of("DESCRIPTION"),
+        open_editor: matches.is_present("edit_description"),
+        edit_description: matches.value_of("editor"),
+        template: matches.value_of("template"),
+        parents: matches.is_present("parents"),
+        ..Default::default()
+    }
+}
+
+/// Creates a directory (`mkdiri`) or an empty file (`touchi`) and writes its description.
+///
+/// The description is taken from `args.set_description`, from the template
+/// `args.template`, or written in the editor if `args.open_editor` is set;
+/// without any of them the path is only created.
+///
+/// # Arguments
+///
+/// * `args` - A reference to the `LsiArgs` struct containing the current configuration.
+/// * `kind` - Whether to create a directory or a file.
+///
+/// # Errors
+///
+/// Returns an error if the path cannot be created or its description cannot be written.
+pub fn create(args: &LsiArgs, kind: LsiPathKind) -> Result<()> {
+    let config = args.config_path.and_then(|path| read_config(path.to_string()));
+    let storage = storage::open(config.as_ref().and_then(|c| c.storage.as_ref()))?;
+    // Resolve the template first so that an unknown name does not leave a bare path behind.
+    let template = args
+        .template
+        .map(|name| template::lookup(config.as_ref(), name))
+        .transpose()?;
+    let path = Path::new(args.path);
+
+    match kind {
+        LsiPathKind::Dir => {
+            let created = match args.parents {
+                true => std::fs::create_dir_all(path),
+                false => std::fs::create_dir(path),
+            };
+            created.with_context(|| format!("Failed to create directory: {}", path.display()))?;
+        }
+        LsiPathKind::File => {
+            if let Some(parent) = path.parent().filter(|p| args.parents && !p.as_os_str().is_empty()) {
+                std::fs::create_dir_all(parent)
+                    .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+            }
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .with_context(|| format!("Failed to create file: {}", path.display()))?;
+        }
+    }
+
+    let seed = template.map(|t| template::render(t, path));
+    match (args.set_description, seed, args.open_editor) {
+        (Some(d), _, _) => write_description(storage.as_ref(), path, d.to_string()),
+        (None, seed, true) => launch_editor(storage.as_ref(), path, args.edit_description, seed.as_deref()),
+        (None, Some(s), false) => write_description(storage.as_ref(), path, s),
+        (None, None, false) => Ok(()),
+    }
+}
+
+/// Expands the given paths, resolving glob patterns that do not name an existing path.
+///
+/// Patterns without any match are kept as they are, so that writing to them reports
+/// the missing path.
+///
+/// # Arguments
+///
+/// * `paths` - The paths or glob patterns given on the command line.
+///
+/// # Errors
+///
+/// Returns an error if a glob pattern is malformed.
+///
+/// # Returns
+///
+/// The expanded list of paths.
+pub fn expand_paths(paths: &[&str]) -> Result<Vec<PathBuf>> {
+    let mut expanded = Vec::new();
+    for &p in paths {
+        let is_pattern = p.contains(['*', '?', '[']);
+        if !is_pattern || Path::new(p).exists() {
+            expanded.push(PathBuf::from(p));
+            continue;
+        }
+        let matches = glob::glob(p)
+            .with_context(|| format!("Invalid glob pattern: {}", p))?
+            .filter_map(|m| m.ok())
+            .collect::<Vec<_>>();
+        match matches.is_empty() {
+            true => expanded.push(PathBuf::from(p)),
+            false => expanded.extend(matches),
+        }
+    }
+    Ok(expanded)
+}
+
+/// Reads `path<TAB>description` lines, skipping blank lines.
+///
+/// # Arguments
+///
+/// * `reader` - The source of the lines, usually stdin.
+///
+/// # Errors
+///
+/// Returns an error if the input cannot be read or a line has no tab separator.
+///
+/// # Returns
+///
+/// Pairs of path and description in input order.
+fn read_descriptions<R: BufRead>(reader: R) -> Result<Vec<(PathBuf, String)>> {
+    let mut entries = Vec::new();
+    for (i, line) in reader.lines().enumerate() {
+        let line = line.with_context(|| "Failed to read descriptions from stdin")?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (path, description) = line
+            .split_once('\t')
+            .ok_or_else(|| LsiError::FileOperationFailed(format!("Missing tab separator on line {}", i + 1)))?;
+        entries.push((PathBuf::from(path), description.to_string()));
+    }
+    Ok(entries)
+}
+
+/// Writes each description to its path, continuing past failures.
+///
+/// # Arguments
+///
+/// * `storage` - The storage backend holding the descriptions.
+/// * `entries` - Pairs of target path and description.
+/// * `edit` - How each description is combined with the existing one.
+///
+/// # Errors
+///
+/// Returns `LsiError::FileOperationFailed` if any description could not be written;
+/// the individual errors are printed as they occur.
+fn write_descriptions(
+    storage: &dyn DescriptionStorage,
+    entries: &[(PathBuf, String)],
+    edit: DescriptionEdit,
+) -> Result<()> {
+    let mut failed = 0;
+    for (path, description) in entries {
+        if let Err(e) = edit_description(storage, path, edit, description) {
+            eprintln!("Error: {:#}", e);
+            failed += 1;
+        }
+    }
+    match failed {
+        0 => Ok(()),
+        n => Err(LsiError::FileOperationFailed(format!(
+            "{} of {} descriptions could not be written",
+            n,
+            entries.len()
+        ))
+        .into()),
+    }
+}
+
+/// Removes the description of each path, continuing past failures.
+///
+/// # Arguments
+///
+/// * `storage` - The storage backend holding the descriptions.
+/// * `paths` - The paths whose descriptions are removed.
+///
+/// # Errors
+///
+/// Returns `LsiError::FileOperationFailed` if any description could not be removed;
+/// the individual errors are printed as they occur.
+fn remove_descriptions(storage: &dyn DescriptionStorage, paths: &[PathBuf]) -> Result<()> {
+    let mut failed = 0;
+    for path in paths {
+        if let Err(e) = remove_description(storage, path) {
+            eprintln!("Error: {:#}", e);
+            failed += 1;
+        }
+    }
+    match failed {
+        0 => Ok(()),
+        n => Err(LsiError::FileOperationFailed(format!(
+            "{} of {} descriptions could not be removed",
+            n,
+            paths.len()
+        ))
+        .into()),
+    }
 }
 
 /// Handles the description setting or editing logic.
@@ -36,54 +317,177 @@ pub fn run(args: &LsiArgs) -> Result<()> {
 ///
 /// # Arguments
 ///
+/// * `storage` - The storage backend holding the descriptions.
 /// * `path` - A reference to the target `PathBuf`.
 /// * `description` - An optional description string to set.
-/// * `editor` - An optional text editor command to use.
+/// * `edit` - How `description` is combined with the existing description.
+/// * `editor` - An optional text editor command to use instead of `$VISUAL`/`$EDITOR`.
 ///
 /// # Returns
 ///
 /// Returns a `Result` which is `Ok` if the operation is successful or an error otherwise.
-fn mkdiri_description(path: &Path, description: Option<&str>, editor: Option<&str>) -> Result<()> {
+fn mkdiri_description(
+    storage: &dyn DescriptionStorage,
+    path: &Path,
+    description: Option<&str>,
+    edit: DescriptionEdit,
+    editor: Option<&str>,
+) -> Result<()> {
     match description {
-        Some(d) => write_description(path, d.to_string()),
-        None => match editor {
-            Some(e) => launch_editor(path, e),
-            None => Err(LsiError::FailedLaunchEditor("No editor specified".to_string()).into()),
-        },
+        Some(d) => edit_description(storage, path, edit, d),
+        None => launch_editor(storage, path, editor, None),
     }
 }
 
 /// Launches a text editor to edit the description of a directory or file.
 ///
-/// This function attempts to launch the specified text editor with the path to
-/// the description file as an argument.
+/// The current description is copied to a temporary file, which is opened in the
+/// editor. The description is only written back if the content changed, and a
+/// line diff of the change is printed. Clearing the file removes the description.
 ///
 /// # Arguments
 ///
+/// * `storage` - The storage backend holding the descriptions.
 /// * `path` - A reference to the target `PathBuf`.
-/// * `editor` - The text editor command to launch.
+/// * `editor` - An optional text editor command, overriding `$VISUAL` and `$EDITOR`.
+/// * `seed` - Text to start editing from instead of the current description.
 ///
 /// # Returns
 ///
-/// Returns a `Result` which is `Ok` if the command is launched successfully or an error otherwise.
-fn launch_editor(path: &Path, editor: &str) -> Result<()> {
-    let mut path = path.canonicalize()?;
-    let filepath = match path.is_dir() {
-        true => format!("{}/.description.lsi", path.to_str().unwrap()),
-        false => {
-            let filename = path.file_name().unwrap().to_str().unwrap().to_string();
-            path.pop();
-            path.push(".file_description_lsi");
-            if !path.is_dir() {
-                std::fs::create_dir(path.to_str().unwrap())?;
-            }
-            format!("{}/.{}.lsi", path.to_str().unwrap(), filename)
+/// Returns a `Result` which is `Ok` if the editor exits successfully or an error otherwise.
+fn launch_editor(
+    storage: &dyn DescriptionStorage,
+    path: &Path,
+    editor: Option<&str>,
+    seed: Option<&str>,
+) -> Result<()> {
+    if !path.exists() {
+        return Err(LsiError::PathNotFound)
+            .with_context(|| format!("Failed to edit description: {}", path.display()));
+    }
+
+    let editor = resolve_editor(editor);
+    let original = read_current_description(storage, path)?;
+
+    let mut tmp = tempfile::Builder::new()
+        .prefix("lsi-")
+        .suffix(".lsi")
+        .tempfile()
+        .with_context(|| "Failed to create a temporary file")?;
+    let initial = seed.unwrap_or(&original);
+    tmp.write_all(initial.as_bytes())?;
+    if !initial.is_empty() {
+        tmp.write_all(b"\n")?;
+    }
+    tmp.flush()?;
+
+    println!("Exec: {} {}", &editor, tmp.path().display());
+    // Run through the shell so that editor commands with arguments (`code --wait`) work.
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(format!("{} \"$@\"", editor))
+        .arg(&editor)
+        .arg(tmp.path())
+        .status()
+        .map_err(|e| LsiError::FailedLaunchEditor(e.to_string()))?;
+    if !status.success() {
+        return Err(LsiError::FailedLaunchEditor(format!("{} exited with {}", editor, status)).into());
+    }
+
+    let edited = std::fs::read_to_string(tmp.path())
+        .with_context(|| format!("Failed to read edited description: {}", tmp.path().display()))?;
+    let edited = edited.trim();
+    if edited == original {
+        println!("No changes: {}", path.display());
+        return Ok(());
+    }
+
+    print_diff(&original, edited);
+    match edited.is_empty() {
+        true => remove_description(storage, path),
+        false => storage
+            .write(path, edited)
+            .map(|location| println!("Success: Write description to {}", location)),
+    }
+}
+
+/// Determines the editor command: the explicit one, then `$VISUAL`, `$EDITOR` and `vi`.
+fn resolve_editor(editor: Option<&str>) -> String {
+    editor
+        .map(|e| e.to_string())
+        .or_else(|| env::var("VISUAL").ok())
+        .or_else(|| env::var("EDITOR").ok())
+        .filter(|e| !e.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string())
+}
+
+/// Prints a line diff between the old and new description, followed by a summary.
+fn print_diff(old: &str, new: &str) {
+    let old: Vec<&str> = if old.is_empty() { Vec::new() } else { old.lines().collect() };
+    let new: Vec<&str> = if new.is_empty() { Vec::new() } else { new.lines().collect() };
+
+    // Longest common subsequence table, filled from the end.
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = match old[i] == new[j] {
+                true => lcs[i + 1][j + 1] + 1,
+                false => lcs[i + 1][j].max(lcs[i][j + 1]),
+            };
         }
-    };
-    println!("Exec: {} {}", &editor, &filepath);
+    }
+
+    let (mut i, mut j, mut added, mut removed) = (0, 0, 0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            println!("- {}", old[i]);
+            removed += 1;
+            i += 1;
+        } else {
+            println!("+ {}", new[j]);
+            added += 1;
+            j += 1;
+        }
+    }
+    println!("{} line(s) added, {} line(s) removed", added, removed);
+}
 
-    // exec replaces the current process, so if it returns, it's an error
-    let err = Command::new(editor).arg(filepath).exec();
-    // We only reach this point if exec failed
-    Err(LsiError::FailedLaunchEditor(format!("Failed to launch editor: {}", err)).into())
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::touch;
+
+    #[test]
+    fn read_descriptions_splits_at_the_first_tab() {
+        let input = "a.txt\tfirst\tsecond\n\n  \nruns/b\tbaseline\n";
+        assert_eq!(
+            read_descriptions(input.as_bytes()).unwrap(),
+            [
+                (PathBuf::from("a.txt"), "first\tsecond".to_string()),
+                (PathBuf::from("runs/b"), "baseline".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn read_descriptions_rejects_lines_without_a_tab() {
+        let err = read_descriptions("a.txt\tok\nb.txt ok\n".as_bytes()).unwrap_err();
+        assert!(err.to_string().contains("line 2"));
+    }
+
+    #[test]
+    fn expand_paths_resolves_globs_and_keeps_the_rest() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = touch(dir.path(), "a.txt");
+        let b = touch(dir.path(), "b.txt");
+        touch(dir.path(), "c.log");
+        let pattern = dir.path().join("*.txt");
+        let missing = dir.path().join("*.csv");
+        let paths = [pattern.to_str().unwrap(), missing.to_str().unwrap(), "plain"];
+
+        assert_eq!(expand_paths(&paths).unwrap(), [a, b, missing, PathBuf::from("plain")]);
+    }
 }
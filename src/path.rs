@@ -1,9 +1,12 @@
 //! This module provides types and functions for handling file paths and their metadata.
 //! It includes functionality for path comparison, sorting, and description management.
 
+use crate::git::GitStatus;
+use crate::metadata::{compare_values, parse_tags, TAGS_FIELD};
 use anyhow::Result;
 use regex::Regex;
 use std::cmp::Ordering;
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 use unicode_width::UnicodeWidthStr;
 
@@ -23,6 +26,10 @@ pub struct LsiPath {
     path: PathBuf,
     /// Optional description for the path
     description: Option<String>,
+    /// Key/value fields from the description's front matter
+    fields: BTreeMap<String, String>,
+    /// The git status, if it was requested and the path is in a repository
+    git_status: Option<GitStatus>,
     /// The kind of path (file or directory)
     pub kind: LsiPathKind,
     /// The mode used for sorting
@@ -50,6 +57,8 @@ impl LsiPath {
         Self {
             path,
             description: None,
+            fields: BTreeMap::new(),
+            git_status: None,
             kind,
             sort_mode: sort_mode.to_string(),
         }
@@ -83,6 +92,15 @@ impl LsiPath {
             .unwrap_or("")
     }
 
+    /// Gets the underlying path.
+    ///
+    /// # Returns
+    ///
+    /// The path as a `Path` reference.
+    pub fn as_path(&self) -> &Path {
+        &self.path
+    }
+
     /// Gets the absolute path as a String.
     ///
     /// # Returns
@@ -114,6 +132,64 @@ impl LsiPath {
         &self.description
     }
 
+    /// Sets the key/value fields of the path.
+    ///
+    /// # Arguments
+    ///
+    /// * `fields` - The fields parsed from the description's front matter.
+    pub fn set_fields(&mut self, fields: BTreeMap<String, String>) {
+        self.fields = fields;
+    }
+
+    /// Gets the value of a key/value field.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The field name.
+    ///
+    /// # Returns
+    ///
+    /// The value as an Option containing a string slice.
+    pub fn get_field(&self, key: &str) -> Option<&str> {
+        self.fields.get(key).map(|v| v.as_str())
+    }
+
+    /// Gets all key/value fields of the path.
+    ///
+    /// # Returns
+    ///
+    /// A reference to the fields, ordered by key.
+    pub fn get_fields(&self) -> &BTreeMap<String, String> {
+        &self.fields
+    }
+
+    /// Sets the git status of the path.
+    ///
+    /// # Arguments
+    ///
+    /// * `status` - The status of the path in its repository.
+    pub fn set_git_status(&mut self, status: GitStatus) {
+        self.git_status = Some(status);
+    }
+
+    /// Gets the git status of the path.
+    ///
+    /// # Returns
+    ///
+    /// The status, or `None` if it was not requested or the path is not in a repository.
+    pub fn get_git_status(&self) -> Option<GitStatus> {
+        self.git_status
+    }
+
+    /// Gets the tags of the path, stored in its `tags` field.
+    ///
+    /// # Returns
+    ///
+    /// The tag names, or an empty vector if the path has no tags.
+    pub fn get_tags(&self) -> Vec<String> {
+        self.get_field(TAGS_FIELD).map(parse_tags).unwrap_or_default()
+    }
+
     /// Gets the plain description (without special characters) of the path.
     ///
     /// # Returns
@@ -163,6 +239,9 @@ impl LsiPath {
 
 impl Ord for LsiPath {
     fn cmp(&self, other: &Self) -> Ordering {
+        if let Some(key) = self.get_sort_mode().strip_prefix("k:") {
+            return compare_fields(self, other, key);
+        }
         let (name1, name2) = format_for_eq(self, other);
         name1.cmp(&name2)
     }
@@ -176,11 +255,44 @@ impl PartialOrd for LsiPath {
 
 impl PartialEq for LsiPath {
     fn eq(&self, other: &Self) -> bool {
-        let (name1, name2) = format_for_eq(self, other);
-        name1 == name2
+        self.cmp(other) == Ordering::Equal
     }
 }
 
+/// Compares two paths by a key/value field.
+///
+/// Directories come before files as in the other sort modes. Paths without the field
+/// come last, and ties are broken by name. A `:desc` suffix on `key` reverses the order
+/// of the field values.
+///
+/// # Arguments
+///
+/// * `path1` - The first LsiPath to be compared.
+/// * `path2` - The second LsiPath to be compared.
+/// * `key` - The field to compare, optionally followed by `:desc`.
+///
+/// # Returns
+///
+/// The ordering of the two paths.
+fn compare_fields(path1: &LsiPath, path2: &LsiPath, key: &str) -> Ordering {
+    let (key, descending) = match key.strip_suffix(":desc") {
+        Some(k) => (k, true),
+        None => (key, false),
+    };
+    let by_value = match (path1.get_field(key), path2.get_field(key)) {
+        (Some(a), Some(b)) if descending => compare_values(b, a),
+        (Some(a), Some(b)) => compare_values(a, b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    };
+    let kind = |p: &LsiPath| p.kind == LsiPathKind::File;
+    kind(path1)
+        .cmp(&kind(path2))
+        .then(by_value)
+        .then_with(|| path1.file_name().cmp(path2.file_name()))
+}
+
 /// Formats the paths for comparison.
 ///
 /// # Arguments
@@ -0,0 +1,103 @@
+//! This module implements `lsi search`, which finds the paths under a directory tree
+//! whose description matches a regular expression.
+
+use crate::colors::Colors;
+use crate::config::read_config;
+use crate::errors::LsiError;
+use crate::fs::walk_dirs;
+use crate::storage::{self, DescriptionStorage};
+use crate::LsiArgs;
+use anyhow::{Context, Result};
+use regex::{Regex, RegexBuilder};
+use std::path::{Path, PathBuf};
+
+/// Runs `lsi search` based on the provided arguments.
+///
+/// Every match is printed as the path relative to `args.path` followed by the
+/// matching description lines, with the matches highlighted.
+///
+/// # Arguments
+///
+/// * `args` - A reference to the `LsiArgs` struct containing the current configuration.
+///
+/// # Errors
+///
+/// Returns an error if the pattern is invalid or the tree cannot be read.
+pub fn run(args: &LsiArgs) -> Result<()> {
+    let config = args.config_path.and_then(|path| read_config(path.to_string()));
+    let colors = Colors::new(config.as_ref().and_then(|c| c.colors.as_ref()));
+    let storage = storage::open(config.as_ref().and_then(|c| c.storage.as_ref()))?;
+
+    let pattern = args.desc_match.unwrap_or_default();
+    let re = RegexBuilder::new(pattern)
+        .case_insensitive(args.ignore_case)
+        .build()
+        .map_err(|_| LsiError::InvalidFilter(pattern.to_string()))?;
+
+    // Backends such as the manifest list canonical paths, so the root must be canonical too.
+    let root = Path::new(args.path)
+        .canonicalize()
+        .map_err(|_| LsiError::PathNotFound)
+        .with_context(|| format!("Failed to search: {}", args.path))?;
+    for (path, lines) in search(storage.as_ref(), &root, &re)? {
+        let relative = path.strip_prefix(&root).unwrap_or(&path);
+        let name = match relative.as_os_str().is_empty() {
+            true => ".".to_string(),
+            false => relative.display().to_string(),
+        };
+        let color = match path.is_dir() {
+            true => &colors.dir,
+            false => &colors.file,
+        };
+        println!("{}{}{}", color, name, colors.end);
+        for line in lines {
+            let highlighted = re.replace_all(&line, |caps: &regex::Captures| {
+                format!("{}{}{}", colors.red, &caps[0], colors.end)
+            });
+            println!("    {}", highlighted);
+        }
+    }
+    Ok(())
+}
+
+/// Finds the paths under `root` (including `root` itself) whose description matches `re`.
+///
+/// # Arguments
+///
+/// * `storage` - The storage backend holding the descriptions.
+/// * `root` - The directory tree to search.
+/// * `re` - The pattern to look for, matched line by line.
+///
+/// # Errors
+///
+/// Returns an error if `root` or one of its directories cannot be read.
+///
+/// # Returns
+///
+/// The matching paths, in tree order, each with its matching description lines.
+pub fn search(storage: &dyn DescriptionStorage, root: &Path, re: &Regex) -> Result<Vec<(PathBuf, Vec<String>)>> {
+    if !root.exists() {
+        return Err(LsiError::PathNotFound)
+            .with_context(|| format!("Failed to search: {}", root.display()));
+    }
+
+    let mut described = Vec::new();
+    if let Ok(description) = storage.read(root) {
+        described.push((root.to_path_buf(), description));
+    }
+    for dir in walk_dirs(root)? {
+        described.extend(storage.list(&dir)?);
+    }
+
+    Ok(described
+        .into_iter()
+        .filter_map(|(path, description)| {
+            let lines: Vec<String> = description
+                .lines()
+                .filter(|line| re.is_match(line))
+                .map(|line| line.to_string())
+                .collect();
+            (!lines.is_empty()).then_some((path, lines))
+        })
+        .collect())
+}
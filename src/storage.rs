@@ -0,0 +1,133 @@
+//! This module defines the `DescriptionStorage` trait, which abstracts where and how
+//! descriptions are persisted, and selects the backend to use from the configuration.
+//!
+//! The listing and mkdiri code only talk to this trait, so alternative layouts can be
+//! added as new backends without touching them.
+
+pub mod atomic;
+pub mod history;
+pub mod manifest;
+pub mod sidecar;
+#[cfg(target_os = "linux")]
+pub mod xattr;
+
+use crate::config::StorageConf;
+use crate::errors::LsiError;
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+pub use atomic::DirLock;
+
+pub use history::HistoryStorage;
+pub use manifest::ManifestStorage;
+pub use sidecar::SidecarStorage;
+#[cfg(target_os = "linux")]
+pub use self::xattr::XattrStorage;
+
+/// Operations every description storage backend has to provide.
+pub trait DescriptionStorage {
+    /// Returns the name of the backend as used in the configuration.
+    fn name(&self) -> &'static str;
+
+    /// Reads the description of `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `LsiError::DescriptionNotFound` (or an I/O error) if `path` has no description.
+    fn read(&self, path: &Path) -> Result<String>;
+
+    /// Writes `content` as the description of `path`, replacing any existing one.
+    ///
+    /// # Returns
+    ///
+    /// A human-readable location of the stored description, used for status messages.
+    fn write(&self, path: &Path, content: &str) -> Result<String>;
+
+    /// Deletes the description of `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `LsiError::DescriptionNotFound` if `path` has no description.
+    fn delete(&self, path: &Path) -> Result<()>;
+
+    /// Lists the descriptions of the direct children of the directory `dir`.
+    ///
+    /// # Returns
+    ///
+    /// Pairs of child path and description, for described children only.
+    fn list(&self, dir: &Path) -> Result<Vec<(PathBuf, String)>>;
+
+    /// Takes the advisory lock guarding the description of `path`.
+    ///
+    /// Writers hold it across read-modify-write cycles, so concurrent jobs annotating
+    /// the same directory do not interleave. The default locks the parent directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `path` cannot be resolved or the lock cannot be taken.
+    fn lock(&self, path: &Path) -> Result<DirLock> {
+        let canonical_path = path
+            .canonicalize()
+            .with_context(|| format!("Failed to canonicalize path: {}", path.display()))?;
+        DirLock::acquire(canonical_path.parent().unwrap_or(&canonical_path))
+    }
+}
+
+/// Creates the storage backend selected by the configuration.
+///
+/// # Arguments
+///
+/// * `conf` - An optional reference to the `[storage]` configuration section.
+///
+/// # Errors
+///
+/// Returns `LsiError::UnknownStorageBackend` if the configured backend does not exist.
+///
+/// # Returns
+///
+/// The selected backend, or the sidecar backend if nothing is configured, recording
+/// the history of changes if `history` is enabled.
+pub fn open(conf: Option<&StorageConf>) -> Result<Box<dyn DescriptionStorage>> {
+    let storage = open_backend(conf.and_then(|c| c.backend.as_deref()).unwrap_or("sidecar"))?;
+    match conf.and_then(|c| c.history).unwrap_or(false) {
+        true => Ok(Box::new(HistoryStorage::new(storage))),
+        false => Ok(storage),
+    }
+}
+
+/// Creates a storage backend by name.
+///
+/// # Arguments
+///
+/// * `backend` - The backend name (`sidecar`, `manifest` or, on Linux, `xattr`).
+///
+/// # Errors
+///
+/// Returns `LsiError::UnknownStorageBackend` if no backend has that name.
+pub fn open_backend(backend: &str) -> Result<Box<dyn DescriptionStorage>> {
+    match backend {
+        "sidecar" => Ok(Box::new(SidecarStorage)),
+        "manifest" => Ok(Box::new(ManifestStorage::new())),
+        #[cfg(target_os = "linux")]
+        "xattr" => Ok(Box::new(XattrStorage::new())),
+        other => Err(LsiError::UnknownStorageBackend(other.to_string()).into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn open_backend_selects_by_name() {
+        assert_eq!(open_backend("sidecar").unwrap().name(), "sidecar");
+        assert_eq!(open_backend("manifest").unwrap().name(), "manifest");
+        let err = open_backend("s3").err().unwrap();
+        assert!(matches!(err.downcast_ref::<LsiError>(), Some(LsiError::UnknownStorageBackend(_))));
+    }
+
+    #[test]
+    fn open_defaults_to_sidecar() {
+        assert_eq!(open(None).unwrap().name(), "sidecar");
+    }
+}
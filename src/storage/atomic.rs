@@ -0,0 +1,169 @@
+//! This module provides the crash-safe building blocks shared by the storage backends:
+//! atomic file replacement and an advisory per-directory lock.
+
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::Write;
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+/// Directories locked by this process, with the number of live guards for each.
+///
+/// `flock` locks taken through different file descriptors conflict even within one
+/// process, so nested acquisitions of the same directory only bump this count.
+static HELD: Mutex<Option<HashMap<PathBuf, (File, usize)>>> = Mutex::new(None);
+
+/// Replaces `path` with `content` atomically.
+///
+/// The content is written to a temporary file in the same directory, flushed to disk
+/// and renamed into place, so readers see either the old or the new content and an
+/// interrupted write never leaves a truncated file behind.
+///
+/// # Arguments
+///
+/// * `path` - The file to replace.
+/// * `content` - The new content.
+///
+/// # Errors
+///
+/// Returns an error if the temporary file cannot be written, synced or renamed.
+pub fn write_atomic(path: &Path, content: &[u8]) -> Result<()> {
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+    let mut tmp = tempfile::Builder::new()
+        .prefix(".lsi-")
+        .suffix(".tmp")
+        .tempfile_in(dir)
+        .with_context(|| format!("Failed to create temporary file in {}", dir.display()))?;
+
+    // Temporary files are private; keep the permissions of the file being replaced, or
+    // give a new file the ones `File::create` would.
+    let permissions = fs::metadata(path)
+        .map(|m| m.permissions())
+        .unwrap_or_else(|_| fs::Permissions::from_mode(0o666 & !umask()));
+    tmp.as_file()
+        .set_permissions(permissions)
+        .with_context(|| format!("Failed to set permissions for {}", path.display()))?;
+
+    tmp.write_all(content)
+        .with_context(|| format!("Failed to write temporary file for {}", path.display()))?;
+    tmp.as_file()
+        .sync_all()
+        .with_context(|| format!("Failed to sync temporary file for {}", path.display()))?;
+    tmp.persist(path)
+        .with_context(|| format!("Failed to replace {}", path.display()))?;
+
+    // Persist the rename itself.
+    if let Ok(d) = File::open(dir) {
+        let _ = d.sync_all();
+    }
+    Ok(())
+}
+
+/// Gets the file mode creation mask of the process.
+fn umask() -> u32 {
+    static UMASK: OnceLock<u32> = OnceLock::new();
+    *UMASK.get_or_init(|| {
+        // The mask can only be read by setting it, so it is put back right away; the
+        // strictest mask is set meanwhile so that no file is created too permissive.
+        // SAFETY: `umask` cannot fail.
+        let mask = unsafe { libc::umask(0o077) };
+        unsafe { libc::umask(mask) };
+        mask as u32
+    })
+}
+
+/// An advisory exclusive lock on a directory, released when dropped.
+pub struct DirLock {
+    /// The locked directory.
+    dir: PathBuf,
+}
+
+impl DirLock {
+    /// Blocks until the exclusive lock on `dir` is acquired.
+    ///
+    /// The lock is re-entrant within the process.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory to lock.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory cannot be opened or locked.
+    pub fn acquire(dir: &Path) -> Result<DirLock> {
+        let dir = dir
+            .canonicalize()
+            .with_context(|| format!("Failed to canonicalize path: {}", dir.display()))?;
+        let mut held = HELD.lock().unwrap_or_else(|e| e.into_inner());
+        let held = held.get_or_insert_with(HashMap::new);
+
+        if let Some((_, count)) = held.get_mut(&dir) {
+            *count += 1;
+            return Ok(DirLock { dir });
+        }
+
+        let file = File::open(&dir)
+            .with_context(|| format!("Failed to open directory for locking: {}", dir.display()))?;
+        // SAFETY: `file` is an open descriptor owned by this function.
+        if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX) } != 0 {
+            return Err(std::io::Error::last_os_error())
+                .with_context(|| format!("Failed to lock directory: {}", dir.display()));
+        }
+        held.insert(dir.clone(), (file, 1));
+        Ok(DirLock { dir })
+    }
+}
+
+impl Drop for DirLock {
+    fn drop(&mut self) {
+        let mut held = HELD.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(held) = held.as_mut() {
+            if let Some((_, count)) = held.get_mut(&self.dir) {
+                *count -= 1;
+                if *count == 0 {
+                    // Closing the descriptor releases the flock.
+                    held.remove(&self.dir);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_atomic_keeps_the_permissions_of_the_replaced_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.lsi");
+        fs::write(&path, "old").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+
+        write_atomic(&path, b"new").unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"new");
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o640);
+    }
+
+    #[test]
+    fn write_atomic_creates_files_honouring_the_umask() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.lsi");
+
+        write_atomic(&path, b"new").unwrap();
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o666 & !umask());
+    }
+
+    #[test]
+    fn dir_lock_is_reentrant() {
+        let dir = tempfile::tempdir().unwrap();
+        let outer = DirLock::acquire(dir.path()).unwrap();
+        let inner = DirLock::acquire(dir.path()).unwrap();
+        drop(inner);
+        drop(outer);
+        let _again = DirLock::acquire(dir.path()).unwrap();
+    }
+}
@@ -0,0 +1,195 @@
+//! This module keeps a journal of description changes.
+//!
+//! When `history = true` is set in the `[storage]` section, the configured backend is
+//! wrapped in a `HistoryStorage`, which appends the previous description of a path to
+//! `<parent>/.lsi_history.jsonl` every time it is replaced or removed:
+//!
+//! ```text
+//! {"time":"2024-06-01 12:00:00","user":"alice","name":"run1","previous":"baseline"}
+//! ```
+
+use super::{DescriptionStorage, DirLock};
+use crate::errors::LsiError;
+use crate::fs::read_existing_description;
+use crate::template::{strftime, user};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+/// File name of the per-directory journal.
+pub const HISTORY_FILE: &str = ".lsi_history.jsonl";
+
+/// One recorded change of a description.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// Local time of the change.
+    pub time: String,
+    /// The user who made the change.
+    pub user: String,
+    /// Name of the described entry within its directory.
+    pub name: String,
+    /// The description before the change, `None` if there was none.
+    pub previous: Option<String>,
+}
+
+/// Wraps a backend and records the previous description on every change.
+pub struct HistoryStorage {
+    /// The backend actually storing the descriptions.
+    inner: Box<dyn DescriptionStorage>,
+}
+
+impl HistoryStorage {
+    /// Creates a new `HistoryStorage` around `inner`.
+    pub fn new(inner: Box<dyn DescriptionStorage>) -> Self {
+        Self { inner }
+    }
+
+    /// Appends a journal entry for `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the journal cannot be written.
+    fn record(&self, path: &Path, previous: Option<String>) -> Result<()> {
+        let (journal, name) = journal_path(path)?;
+        let entry = HistoryEntry {
+            time: strftime(c"%Y-%m-%d %H:%M:%S"),
+            user: user(),
+            name,
+            previous,
+        };
+        let mut line = serde_json::to_string(&entry).context("Failed to serialize history entry")?;
+        line.push('\n');
+
+        // Callers hold the directory lock, so appended lines never interleave.
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&journal)
+            .and_then(|mut f| f.write_all(line.as_bytes()))
+            .with_context(|| format!("Failed to write history: {}", journal.display()))
+    }
+}
+
+impl DescriptionStorage for HistoryStorage {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    fn read(&self, path: &Path) -> Result<String> {
+        self.inner.read(path)
+    }
+
+    fn write(&self, path: &Path, content: &str) -> Result<String> {
+        let _lock = self.lock(path)?;
+        // A previous description that cannot be read is not replaced, since it could not be restored.
+        let previous = read_existing_description(self.inner.as_ref(), path)?;
+        let location = self.inner.write(path, content)?;
+        if previous.as_deref() != Some(content.trim()) {
+            self.record(path, previous)?;
+        }
+        Ok(location)
+    }
+
+    fn delete(&self, path: &Path) -> Result<()> {
+        let _lock = self.lock(path)?;
+        let previous = read_existing_description(self.inner.as_ref(), path)?;
+        self.inner.delete(path)?;
+        self.record(path, previous)
+    }
+
+    fn list(&self, dir: &Path) -> Result<Vec<(PathBuf, String)>> {
+        self.inner.list(dir)
+    }
+
+    fn lock(&self, path: &Path) -> Result<DirLock> {
+        self.inner.lock(path)
+    }
+}
+
+/// Resolves the journal that records the changes of `path` and the entry name used in it.
+///
+/// # Errors
+///
+/// Returns an error if `path` cannot be canonicalized or has no parent.
+pub fn journal_path(path: &Path) -> Result<(PathBuf, String)> {
+    let canonical_path = path
+        .canonicalize()
+        .with_context(|| format!("Failed to canonicalize path: {}", path.display()))?;
+    let name = canonical_path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or(LsiError::InvalidPath)?
+        .to_string();
+    let parent = canonical_path.parent().ok_or(LsiError::InvalidPath)?;
+    Ok((parent.join(HISTORY_FILE), name))
+}
+
+/// Reads the recorded changes of `path`.
+///
+/// # Arguments
+///
+/// * `path` - The described file or directory.
+///
+/// # Errors
+///
+/// Returns an error if the journal exists but cannot be read or parsed.
+///
+/// # Returns
+///
+/// The changes of `path`, oldest first; empty if none were recorded.
+pub fn read_history(path: &Path) -> Result<Vec<HistoryEntry>> {
+    let (journal, name) = journal_path(path)?;
+    let file = match fs::File::open(&journal) {
+        Ok(f) => f,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read history: {}", journal.display())),
+    };
+
+    let mut entries = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let line = line.with_context(|| format!("Failed to read history: {}", journal.display()))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry: HistoryEntry = serde_json::from_str(&line)
+            .with_context(|| format!("Failed to parse history: {}", journal.display()))?;
+        if entry.name == name {
+            entries.push(entry);
+        }
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::sidecar::SidecarStorage;
+    use crate::testing::{touch, with_unreadable_description, UNREADABLE};
+
+    #[test]
+    fn write_and_delete_record_the_previous_description() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = touch(dir.path(), "a.txt");
+        let storage = HistoryStorage::new(Box::new(SidecarStorage));
+
+        storage.write(&file, "first").unwrap();
+        storage.write(&file, "second").unwrap();
+        storage.delete(&file).unwrap();
+        let previous: Vec<Option<String>> = read_history(&file).unwrap().into_iter().map(|e| e.previous).collect();
+        assert_eq!(previous, [None, Some("first".to_string()), Some("second".to_string())]);
+    }
+
+    #[test]
+    fn write_keeps_an_unreadable_description() {
+        let dir = tempfile::tempdir().unwrap();
+        let (file, sidecar) = with_unreadable_description(dir.path(), "a.txt");
+
+        let storage = HistoryStorage::new(Box::new(SidecarStorage));
+        assert!(storage.write(&file, "new").is_err());
+        assert!(storage.delete(&file).is_err());
+        assert_eq!(fs::read(&sidecar).unwrap(), UNREADABLE);
+        assert!(read_history(&file).unwrap().is_empty());
+    }
+}
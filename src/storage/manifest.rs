@@ -0,0 +1,236 @@
+//! This module implements the manifest storage backend.
+//!
+//! All descriptions of the entries of a directory are kept in a single `.lsi.toml`
+//! file inside that directory, keyed by entry name. The description of a directory
+//! itself is kept in its own manifest under `.`, so a directory tree carries all of its
+//! descriptions:
+//!
+//! ```toml
+//! [descriptions]
+//! "." = "sweep over learning rates"
+//! "notes.txt" = "hyper parameters"
+//! ```
+
+use super::atomic::{write_atomic, DirLock};
+use super::DescriptionStorage;
+use crate::errors::LsiError;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// File name of the per-directory manifest.
+pub const MANIFEST: &str = ".lsi.toml";
+
+/// Key of the description of the directory holding the manifest.
+const SELF_KEY: &str = ".";
+
+/// On-disk representation of a manifest.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+struct Manifest {
+    /// Descriptions keyed by entry name.
+    #[serde(default)]
+    descriptions: BTreeMap<String, String>,
+}
+
+/// Stores the descriptions of a directory's entries in one manifest file.
+#[derive(Default)]
+pub struct ManifestStorage {
+    /// Parsed manifests keyed by directory, so a listing parses each manifest once.
+    cache: RefCell<HashMap<PathBuf, Manifest>>,
+}
+
+impl ManifestStorage {
+    /// Creates a new `ManifestStorage` with an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Splits `path` into the directory holding its manifest and its key in it: the entry
+    /// name for a file, `.` for a directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `path` cannot be canonicalized or has no parent.
+    fn locate(path: &Path) -> Result<(PathBuf, String)> {
+        let canonical_path = path
+            .canonicalize()
+            .with_context(|| format!("Failed to canonicalize path: {}", path.display()))?;
+        if canonical_path.is_dir() {
+            return Ok((canonical_path, SELF_KEY.to_string()));
+        }
+        let name = canonical_path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or(LsiError::InvalidPath)?
+            .to_string();
+        let parent = canonical_path.parent().ok_or(LsiError::InvalidPath)?;
+        Ok((parent.to_path_buf(), name))
+    }
+
+    /// Loads the manifest of `dir`, returning an empty one if it does not exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the manifest exists but cannot be read or parsed.
+    fn load(&self, dir: &Path) -> Result<Manifest> {
+        if let Some(manifest) = self.cache.borrow().get(dir) {
+            return Ok(manifest.clone());
+        }
+        self.reload(dir)
+    }
+
+    /// Loads the manifest of `dir` from disk, bypassing the cache.
+    ///
+    /// Writers call this while holding the directory lock, so they never
+    /// overwrite descriptions added by another process in the meantime.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the manifest exists but cannot be read or parsed.
+    fn reload(&self, dir: &Path) -> Result<Manifest> {
+        let manifest_path = dir.join(MANIFEST);
+        let manifest = match fs::read_to_string(&manifest_path) {
+            Ok(s) => toml::from_str(&s)
+                .with_context(|| format!("Failed to parse manifest: {}", manifest_path.display()))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Manifest::default(),
+            Err(e) => {
+                return Err(e)
+                    .with_context(|| format!("Failed to read manifest: {}", manifest_path.display()))
+            }
+        };
+
+        self.cache.borrow_mut().insert(dir.to_path_buf(), manifest.clone());
+        Ok(manifest)
+    }
+
+    /// Stores the manifest of `dir`, removing the file when it no longer holds any description.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the manifest cannot be written or removed.
+    fn save(&self, dir: &Path, manifest: Manifest) -> Result<PathBuf> {
+        let manifest_path = dir.join(MANIFEST);
+        if manifest.descriptions.is_empty() {
+            if manifest_path.exists() {
+                fs::remove_file(&manifest_path)
+                    .with_context(|| format!("Failed to remove manifest: {}", manifest_path.display()))?;
+            }
+        } else {
+            let content = toml::to_string(&manifest)
+                .with_context(|| format!("Failed to serialize manifest: {}", manifest_path.display()))?;
+            write_atomic(&manifest_path, content.as_bytes())
+                .with_context(|| format!("Failed to write manifest: {}", manifest_path.display()))?;
+        }
+        self.cache.borrow_mut().insert(dir.to_path_buf(), manifest);
+        Ok(manifest_path)
+    }
+}
+
+impl DescriptionStorage for ManifestStorage {
+    fn name(&self) -> &'static str {
+        "manifest"
+    }
+
+    fn read(&self, path: &Path) -> Result<String> {
+        let (dir, name) = ManifestStorage::locate(path)?;
+        self.load(&dir)?
+            .descriptions
+            .remove(&name)
+            .map(|d| d.trim().to_string())
+            .ok_or_else(|| LsiError::DescriptionNotFound.into())
+    }
+
+    fn write(&self, path: &Path, content: &str) -> Result<String> {
+        let (dir, name) = ManifestStorage::locate(path)?;
+        let _lock = DirLock::acquire(&dir)?;
+        let mut manifest = self.reload(&dir)?;
+        manifest.descriptions.insert(name.clone(), content.to_string());
+        let manifest_path = self.save(&dir, manifest)?;
+        Ok(format!("{} [{}]", manifest_path.display(), name))
+    }
+
+    fn delete(&self, path: &Path) -> Result<()> {
+        let (dir, name) = ManifestStorage::locate(path)?;
+        let _lock = DirLock::acquire(&dir)?;
+        let mut manifest = self.reload(&dir)?;
+        if manifest.descriptions.remove(&name).is_none() {
+            return Err(LsiError::DescriptionNotFound.into());
+        }
+        self.save(&dir, manifest)?;
+        Ok(())
+    }
+
+    fn list(&self, dir: &Path) -> Result<Vec<(PathBuf, String)>> {
+        let dir = dir
+            .canonicalize()
+            .with_context(|| format!("Failed to canonicalize path: {}", dir.display()))?;
+        // Child directories keep their descriptions in their own manifests.
+        let mut descriptions: Vec<(PathBuf, String)> = self
+            .load(&dir)?
+            .descriptions
+            .into_iter()
+            .filter(|(name, _)| name != SELF_KEY)
+            .map(|(name, description)| (dir.join(name), description.trim().to_string()))
+            .filter(|(path, _)| path.is_file())
+            .collect();
+        let entries = fs::read_dir(&dir).with_context(|| format!("Failed to read directory: {}", dir.display()))?;
+        for entry in entries {
+            let path = entry
+                .with_context(|| format!("Failed to read directory entry in {}", dir.display()))?
+                .path();
+            if path.is_dir() {
+                if let Ok(description) = self.read(&path) {
+                    descriptions.push((path, description));
+                }
+            }
+        }
+        descriptions.sort();
+        Ok(descriptions)
+    }
+
+    fn lock(&self, path: &Path) -> Result<DirLock> {
+        let (dir, _) = ManifestStorage::locate(path)?;
+        DirLock::acquire(&dir)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::assert_round_trip;
+
+    #[test]
+    fn round_trip() {
+        assert_round_trip(&ManifestStorage::new());
+    }
+
+    #[test]
+    fn the_manifest_is_removed_with_its_last_description() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = ManifestStorage::new();
+        storage.write(dir.path(), "sweep").unwrap();
+        assert!(dir.path().join(MANIFEST).exists());
+        storage.delete(dir.path()).unwrap();
+        assert!(!dir.path().join(MANIFEST).exists());
+    }
+
+    #[test]
+    fn directory_descriptions_live_in_their_own_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("runs");
+        fs::create_dir_all(root.join("a")).unwrap();
+        let storage = ManifestStorage::new();
+
+        storage.write(&root, "sweep").unwrap();
+        storage.write(&root.join("a"), "baseline").unwrap();
+        assert!(!dir.path().join(MANIFEST).exists());
+        assert!(root.join("a").join(MANIFEST).exists());
+        assert_eq!(storage.read(&root).unwrap(), "sweep");
+
+        let root = root.canonicalize().unwrap();
+        assert_eq!(storage.list(&root).unwrap(), [(root.join("a"), "baseline".to_string())]);
+    }
+}
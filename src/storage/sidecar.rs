@@ -0,0 +1,172 @@
+//! This module implements the default sidecar storage backend.
+//!
+//! A directory description lives in `<dir>/.description.lsi` and a file description
+//! lives in `<parent>/.file_description_lsi/.<filename>.lsi`.
+
+use super::atomic::write_atomic;
+use super::DescriptionStorage;
+use crate::errors::LsiError;
+use anyhow::{Context, Result};
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+/// File name of a directory description.
+pub const DIR_DESCRIPTION: &str = ".description.lsi";
+
+/// Name of the directory holding file descriptions.
+pub const FILE_DESCRIPTION_DIR: &str = ".file_description_lsi";
+
+/// Stores descriptions in hidden sidecar files next to the described paths.
+pub struct SidecarStorage;
+
+impl SidecarStorage {
+    /// Resolves the sidecar file that holds the description of `path`.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The described file or directory.
+    /// * `create_dir` - Whether to create `.file_description_lsi` if it does not exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `path` cannot be canonicalized or is not valid UTF-8.
+    pub fn description_path(path: &Path, create_dir: bool) -> Result<PathBuf> {
+        let canonical_path = path
+            .canonicalize()
+            .with_context(|| format!("Failed to canonicalize path: {}", path.display()))?;
+
+        if canonical_path.is_dir() {
+            return Ok(canonical_path.join(DIR_DESCRIPTION));
+        }
+
+        let filename = canonical_path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or(LsiError::InvalidPath)?;
+        let mut desc_dir = canonical_path
+            .parent()
+            .ok_or(LsiError::InvalidPath)?
+            .join(FILE_DESCRIPTION_DIR);
+
+        if create_dir && !desc_dir.is_dir() {
+            fs::create_dir(&desc_dir)
+                .with_context(|| format!("Failed to create directory: {}", desc_dir.display()))?;
+        }
+
+        desc_dir.push(format!(".{}.lsi", filename));
+        Ok(desc_dir)
+    }
+}
+
+impl DescriptionStorage for SidecarStorage {
+    fn name(&self) -> &'static str {
+        "sidecar"
+    }
+
+    fn read(&self, path: &Path) -> Result<String> {
+        let desc_path = SidecarStorage::description_path(path, false)?;
+        read_description_file(&desc_path)
+    }
+
+    fn write(&self, path: &Path, content: &str) -> Result<String> {
+        let _lock = self.lock(path)?;
+        let desc_path = SidecarStorage::description_path(path, true)?;
+
+        write_atomic(&desc_path, content.as_bytes())
+            .with_context(|| format!("Failed to write to description file: {}", desc_path.display()))?;
+
+        Ok(desc_path.display().to_string())
+    }
+
+    fn delete(&self, path: &Path) -> Result<()> {
+        let _lock = self.lock(path)?;
+        let desc_path = SidecarStorage::description_path(path, false)?;
+        if !desc_path.is_file() {
+            return Err(LsiError::DescriptionNotFound.into());
+        }
+        fs::remove_file(&desc_path)
+            .with_context(|| format!("Failed to remove description file: {}", desc_path.display()))?;
+
+        // Drop `.file_description_lsi` once its last description is gone.
+        if let Some(desc_dir) = desc_path.parent().filter(|d| d.ends_with(FILE_DESCRIPTION_DIR)) {
+            let is_empty = fs::read_dir(desc_dir)
+                .map(|mut entries| entries.next().is_none())
+                .unwrap_or(false);
+            if is_empty {
+                fs::remove_dir(desc_dir)
+                    .with_context(|| format!("Failed to remove directory: {}", desc_dir.display()))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn list(&self, dir: &Path) -> Result<Vec<(PathBuf, String)>> {
+        let mut descriptions = Vec::new();
+        let entries = fs::read_dir(dir)
+            .with_context(|| format!("Failed to read directory: {}", dir.display()))?;
+
+        for entry in entries {
+            let path = entry
+                .with_context(|| format!("Failed to read directory entry in {}", dir.display()))?
+                .path();
+            if let Ok(description) = self.read(&path) {
+                descriptions.push((path, description));
+            }
+        }
+
+        descriptions.sort();
+        Ok(descriptions)
+    }
+}
+
+/// Helper function to read a description file.
+///
+/// # Arguments
+///
+/// * `path` - The path to the description file.
+///
+/// # Returns
+///
+/// A Result containing the description as a String.
+fn read_description_file(path: &Path) -> Result<String> {
+    let file = File::open(path)
+        .with_context(|| format!("Failed to open description file: {}", path.display()))?;
+
+    let reader = BufReader::new(file);
+    let content: Result<String> = reader.lines()
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map(|lines| lines.join("\n"))
+        .with_context(|| format!("Failed to read lines from description file: {}", path.display()));
+
+    Ok(content?.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{assert_round_trip, touch};
+
+    #[test]
+    fn round_trip() {
+        assert_round_trip(&SidecarStorage);
+    }
+
+    #[test]
+    fn description_files_are_hidden_next_to_the_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let file = touch(&root, "a.txt");
+
+        assert_eq!(
+            SidecarStorage::description_path(&file, false).unwrap(),
+            root.join(FILE_DESCRIPTION_DIR).join(".a.txt.lsi")
+        );
+        assert_eq!(SidecarStorage::description_path(&root, false).unwrap(), root.join(DIR_DESCRIPTION));
+        assert!(!root.join(FILE_DESCRIPTION_DIR).exists());
+
+        SidecarStorage.write(&file, "notes").unwrap();
+        SidecarStorage.delete(&file).unwrap();
+        assert!(!root.join(FILE_DESCRIPTION_DIR).exists());
+    }
+}
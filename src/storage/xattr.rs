@@ -0,0 +1,125 @@
+//! This module implements the extended-attribute storage backend (Linux only).
+//!
+//! A description is kept in the `user.lsi.description` attribute of the described
+//! file or directory itself, so it follows the path on `mv`. On filesystems without
+//! user xattr support, descriptions fall back to sidecar files.
+
+use super::{DescriptionStorage, SidecarStorage};
+use anyhow::{Context, Result};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Name of the extended attribute holding the description.
+pub const ATTRIBUTE: &str = "user.lsi.description";
+
+/// Stores descriptions in an extended attribute, falling back to sidecar files.
+pub struct XattrStorage {
+    /// Backend used where extended attributes are not supported.
+    fallback: SidecarStorage,
+}
+
+impl XattrStorage {
+    /// Creates a new `XattrStorage` backed by sidecar files as a fallback.
+    pub fn new() -> Self {
+        Self {
+            fallback: SidecarStorage,
+        }
+    }
+}
+
+impl Default for XattrStorage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns whether an I/O error means the filesystem does not support user xattrs.
+fn is_unsupported(e: &io::Error) -> bool {
+    matches!(e.raw_os_error(), Some(libc::ENOTSUP) | Some(libc::EPERM))
+}
+
+impl DescriptionStorage for XattrStorage {
+    fn name(&self) -> &'static str {
+        "xattr"
+    }
+
+    fn read(&self, path: &Path) -> Result<String> {
+        match xattr::get(path, ATTRIBUTE) {
+            Ok(Some(value)) => Ok(String::from_utf8_lossy(&value).trim().to_string()),
+            Ok(None) => self.fallback.read(path),
+            Err(e) if is_unsupported(&e) => self.fallback.read(path),
+            Err(e) => Err(e).with_context(|| format!("Failed to read {} of {}", ATTRIBUTE, path.display())),
+        }
+    }
+
+    fn write(&self, path: &Path, content: &str) -> Result<String> {
+        match xattr::set(path, ATTRIBUTE, content.as_bytes()) {
+            Ok(()) => {
+                // A leftover sidecar would be shadowed by the attribute, so drop it.
+                if let Ok(desc_path) = SidecarStorage::description_path(path, false) {
+                    if desc_path.is_file() {
+                        self.fallback.delete(path)?;
+                    }
+                }
+                Ok(format!("{} ({})", path.display(), ATTRIBUTE))
+            }
+            Err(e) if is_unsupported(&e) => self.fallback.write(path, content),
+            Err(e) => Err(e).with_context(|| format!("Failed to set {} of {}", ATTRIBUTE, path.display())),
+        }
+    }
+
+    fn delete(&self, path: &Path) -> Result<()> {
+        match xattr::remove(path, ATTRIBUTE) {
+            Ok(()) => Ok(()),
+            Err(e) if e.raw_os_error() == Some(libc::ENODATA) || is_unsupported(&e) => {
+                self.fallback.delete(path)
+            }
+            Err(e) => Err(e).with_context(|| format!("Failed to remove {} of {}", ATTRIBUTE, path.display())),
+        }
+    }
+
+    fn list(&self, dir: &Path) -> Result<Vec<(PathBuf, String)>> {
+        let mut descriptions = Vec::new();
+        let entries = fs::read_dir(dir)
+            .with_context(|| format!("Failed to read directory: {}", dir.display()))?;
+
+        for entry in entries {
+            let path = entry
+                .with_context(|| format!("Failed to read directory entry in {}", dir.display()))?
+                .path();
+            if let Ok(description) = self.read(&path) {
+                descriptions.push((path, description));
+            }
+        }
+
+        descriptions.sort();
+        Ok(descriptions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{assert_round_trip, touch};
+
+    #[test]
+    fn round_trip() {
+        assert_round_trip(&XattrStorage::new());
+    }
+
+    #[test]
+    fn write_replaces_a_leftover_sidecar() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = touch(dir.path(), "a.txt");
+        SidecarStorage.write(&file, "old").unwrap();
+
+        let storage = XattrStorage::new();
+        storage.write(&file, "new").unwrap();
+        assert_eq!(storage.read(&file).unwrap(), "new");
+        // Without user xattr support the sidecar itself holds the new description.
+        if xattr::get(&file, ATTRIBUTE).unwrap_or_default().is_some() {
+            assert!(SidecarStorage.read(&file).is_err());
+        }
+    }
+}
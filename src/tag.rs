@@ -0,0 +1,104 @@
+//! This module implements `lsi tag`, which adds tags to (or removes them from) paths.
+//!
+//! Tags live in the `tags` field of the description's front matter, so they are kept
+//! by every storage backend and travel with exports like the rest of the description.
+
+use crate::config::read_config;
+use crate::errors::LsiError;
+use crate::fs::{read_current_description, remove_description};
+use crate::metadata::{get_tags, parse_tags, with_tags};
+use crate::mkdiri::expand_paths;
+use crate::storage::{self, DescriptionStorage};
+use crate::LsiArgs;
+use anyhow::{Context, Result};
+use std::path::Path;
+
+/// Runs `lsi tag` based on the provided arguments.
+///
+/// Every path is updated even if another one fails.
+///
+/// # Arguments
+///
+/// * `args` - A reference to the `LsiArgs` struct containing the current configuration.
+///
+/// # Errors
+///
+/// Returns an error if the tags of at least one path could not be updated.
+pub fn run(args: &LsiArgs) -> Result<()> {
+    let config = args.config_path.and_then(|path| read_config(path.to_string()));
+    let storage = storage::open(config.as_ref().and_then(|c| c.storage.as_ref()))?;
+    let tags: Vec<String> = args.tags.iter().flat_map(|t| parse_tags(t)).collect();
+    let paths = expand_paths(&args.paths)?;
+
+    let mut failed = 0;
+    for path in &paths {
+        if let Err(e) = tag_path(storage.as_ref(), path, &tags, args.remove_tags) {
+            eprintln!("Error: {:#}", e);
+            failed += 1;
+        }
+    }
+    match failed {
+        0 => Ok(()),
+        n => Err(LsiError::FileOperationFailed(format!(
+            "{} of {} paths could not be tagged",
+            n,
+            paths.len()
+        ))
+        .into()),
+    }
+}
+
+/// Adds `tags` to the description of `path`, or removes them if `remove` is set.
+///
+/// # Arguments
+///
+/// * `storage` - The storage backend holding the descriptions.
+/// * `path` - The file or directory to tag.
+/// * `tags` - The tags to add or remove.
+/// * `remove` - Whether to remove the tags instead of adding them.
+///
+/// # Errors
+///
+/// Returns an error if the path does not exist, or the description cannot be read or updated.
+pub fn tag_path(storage: &dyn DescriptionStorage, path: &Path, tags: &[String], remove: bool) -> Result<()> {
+    if !path.exists() {
+        return Err(LsiError::PathNotFound)
+            .with_context(|| format!("Failed to tag: {}", path.display()));
+    }
+
+    let _lock = storage.lock(path)?;
+    let current = read_current_description(storage, path)?;
+    let mut new_tags = get_tags(&current);
+    match remove {
+        true => new_tags.retain(|t| !tags.contains(t)),
+        false => new_tags.extend(tags.iter().filter(|t| !new_tags.contains(t)).cloned().collect::<Vec<_>>()),
+    }
+    let content = with_tags(&current, &new_tags)?;
+
+    if content == current {
+        println!("Nothing to do: {}", path.display());
+        return Ok(());
+    }
+    if content.trim().is_empty() {
+        return remove_description(storage, path);
+    }
+    let location = storage.write(path, &content)?;
+    println!("Success: Write description to {}", location);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::sidecar::SidecarStorage;
+    use crate::testing::{with_unreadable_description, UNREADABLE};
+
+    #[test]
+    fn tag_path_keeps_an_unreadable_description() {
+        let dir = tempfile::tempdir().unwrap();
+        let (file, sidecar) = with_unreadable_description(dir.path(), "a.txt");
+
+        assert!(tag_path(&SidecarStorage, &file, &["paper".to_string()], false).is_err());
+        assert_eq!(std::fs::read(&sidecar).unwrap(), UNREADABLE);
+    }
+}
@@ -0,0 +1,185 @@
+//! This module renders the description templates defined in the `[templates]` section
+//! of the configuration file.
+//!
+//! A template is a description skeleton whose placeholders are filled in for the
+//! described path:
+//!
+//! ```toml
+//! [templates]
+//! experiment = """
+//! +++
+//! date = "{date}"
+//! user = "{user}"
+//! host = "{hostname}"
+//! commit = "{git_commit}"
+//! lr = ""
+//! +++
+//! {name}: TODO
+//! """
+//! ```
+
+use crate::config::Config;
+use crate::errors::LsiError;
+use anyhow::Result;
+use std::env;
+use std::ffi::CStr;
+use std::path::Path;
+use std::process::Command;
+
+/// Looks up a template by name in the configuration.
+///
+/// # Arguments
+///
+/// * `config` - The configuration, if one was loaded.
+/// * `name` - The template name.
+///
+/// # Errors
+///
+/// Returns `LsiError::UnknownTemplate` if no template with that name is configured.
+pub fn lookup<'a>(config: Option<&'a Config>, name: &str) -> Result<&'a str> {
+    config
+        .and_then(|c| c.templates.as_ref())
+        .and_then(|t| t.get(name))
+        .map(|t| t.as_str())
+        .ok_or_else(|| LsiError::UnknownTemplate(name.to_string()).into())
+}
+
+/// Fills in the placeholders of a template for `path`.
+///
+/// Supported placeholders are `{date}`, `{time}`, `{user}`, `{hostname}`,
+/// `{git_commit}` (empty outside a git repository), `{name}` and `{path}`.
+///
+/// # Arguments
+///
+/// * `template` - The template text.
+/// * `path` - The path the description is written for.
+///
+/// # Returns
+///
+/// The rendered description.
+pub fn render(template: &str, path: &Path) -> String {
+    let absolute = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let name = absolute
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let placeholders: [(&str, &dyn Fn() -> String); 7] = [
+        ("{date}", &|| strftime(c"%Y-%m-%d")),
+        ("{time}", &|| strftime(c"%H:%M:%S")),
+        ("{user}", &user),
+        ("{hostname}", &hostname),
+        ("{git_commit}", &|| git_commit(&absolute)),
+        ("{name}", &|| name.clone()),
+        ("{path}", &|| absolute.display().to_string()),
+    ];
+
+    let mut rendered = template.trim().to_string();
+    for (placeholder, value) in placeholders.iter() {
+        if rendered.contains(placeholder) {
+            rendered = rendered.replace(placeholder, &value());
+        }
+    }
+    rendered
+}
+
+/// Formats the current local time with `strftime`.
+pub fn strftime(format: &CStr) -> String {
+    let mut buf = [0u8; 64];
+    // SAFETY: `tm` is fully initialized by `localtime_r` before it is read, and
+    // `strftime` writes at most `buf.len()` bytes including the terminating NUL.
+    let len = unsafe {
+        let now = libc::time(std::ptr::null_mut());
+        let mut tm: libc::tm = std::mem::zeroed();
+        if libc::localtime_r(&now, &mut tm).is_null() {
+            return String::new();
+        }
+        libc::strftime(buf.as_mut_ptr().cast(), buf.len(), format.as_ptr(), &tm)
+    };
+    String::from_utf8_lossy(&buf[..len]).to_string()
+}
+
+/// Returns the name of the current user, from the environment or the password database.
+pub fn user() -> String {
+    if let Ok(user) = env::var("USER").or_else(|_| env::var("LOGNAME")) {
+        return user;
+    }
+    // SAFETY: the returned entry is only read before any other password database call.
+    unsafe {
+        let pw = libc::getpwuid(libc::geteuid());
+        if pw.is_null() {
+            return String::new();
+        }
+        CStr::from_ptr((*pw).pw_name).to_string_lossy().to_string()
+    }
+}
+
+/// Returns the host name of the machine.
+fn hostname() -> String {
+    let mut buf = [0u8; 256];
+    // SAFETY: `gethostname` writes at most `buf.len()` bytes into `buf`.
+    if unsafe { libc::gethostname(buf.as_mut_ptr().cast(), buf.len()) } != 0 {
+        return String::new();
+    }
+    CStr::from_bytes_until_nul(&buf)
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Returns the abbreviated commit of the git repository containing `path`, if any.
+fn git_commit(path: &Path) -> String {
+    let dir = match path.is_dir() {
+        true => path,
+        false => path.parent().unwrap_or(path),
+    };
+    Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::touch;
+
+    #[test]
+    fn render_fills_in_the_path_placeholders() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = touch(dir.path(), "run1.txt");
+        let absolute = file.canonicalize().unwrap();
+
+        let rendered = render("\n{name}: TODO\npath = {path}\nunknown = {lr}\n\n", &file);
+        assert_eq!(
+            rendered,
+            format!("run1.txt: TODO\npath = {}\nunknown = {{lr}}", absolute.display())
+        );
+    }
+
+    #[test]
+    fn render_fills_in_the_date_time_and_commit() {
+        let dir = tempfile::tempdir().unwrap();
+        let rendered = render("{date} {time} [{git_commit}]", dir.path());
+        let (date, rest) = rendered.split_once(' ').unwrap();
+        let (time, commit) = rest.split_once(' ').unwrap();
+        assert_eq!(date.len(), "2024-06-01".len());
+        assert_eq!(date.matches('-').count(), 2);
+        assert_eq!(time.matches(':').count(), 2);
+        // A temporary directory is not inside a git repository.
+        assert_eq!(commit, "[]");
+    }
+
+    #[test]
+    fn lookup_finds_configured_templates() {
+        let config: Config = toml::from_str("[templates]\nexperiment = \"{name}: TODO\"\n").unwrap();
+        assert_eq!(lookup(Some(&config), "experiment").unwrap(), "{name}: TODO");
+
+        let err = lookup(Some(&config), "missing").unwrap_err();
+        assert!(matches!(err.downcast_ref::<LsiError>(), Some(LsiError::UnknownTemplate(_))));
+        assert!(lookup(None, "experiment").is_err());
+    }
+}
@@ -0,0 +1,57 @@
+//! Fixtures shared by the unit tests.
+
+use crate::storage::sidecar::{SidecarStorage, FILE_DESCRIPTION_DIR};
+use crate::storage::DescriptionStorage;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The content of an unreadable description: it is not valid UTF-8.
+pub const UNREADABLE: &[u8] = b"caf\xe9 notes";
+
+/// Creates an empty file `name` in `dir`.
+pub fn touch(dir: &Path, name: &str) -> PathBuf {
+    let path = dir.join(name);
+    fs::write(&path, "").unwrap();
+    path
+}
+
+/// Creates a file `name` in `dir` whose sidecar description exists but cannot be read.
+///
+/// # Returns
+///
+/// The file and its sidecar description file, holding `UNREADABLE`.
+pub fn with_unreadable_description(dir: &Path, name: &str) -> (PathBuf, PathBuf) {
+    let file = touch(dir, name);
+    SidecarStorage.write(&file, "placeholder").unwrap();
+    let sidecar = dir.join(FILE_DESCRIPTION_DIR).join(format!(".{}.lsi", name));
+    fs::write(&sidecar, UNREADABLE).unwrap();
+    (file, sidecar)
+}
+
+/// Checks that `storage` writes, reads, lists and deletes the descriptions of a file and
+/// a directory in a fresh directory.
+pub fn assert_round_trip(storage: &dyn DescriptionStorage) {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().canonicalize().unwrap();
+    let file = touch(&root, "a.txt");
+    let run = root.join("run");
+    fs::create_dir(&run).unwrap();
+
+    storage.write(&file, "first\nsecond").unwrap();
+    storage.write(&run, "baseline").unwrap();
+    assert_eq!(storage.read(&file).unwrap(), "first\nsecond");
+    assert_eq!(storage.read(&run).unwrap(), "baseline");
+    assert_eq!(
+        storage.list(&root).unwrap(),
+        [(file.clone(), "first\nsecond".to_string()), (run.clone(), "baseline".to_string())]
+    );
+
+    storage.write(&file, "replaced").unwrap();
+    assert_eq!(storage.read(&file).unwrap(), "replaced");
+
+    storage.delete(&file).unwrap();
+    storage.delete(&run).unwrap();
+    assert!(storage.read(&file).is_err());
+    assert!(storage.delete(&file).is_err());
+    assert!(storage.list(&root).unwrap().is_empty());
+}
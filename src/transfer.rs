@@ -0,0 +1,274 @@
+//! This module exports all descriptions under a directory tree into a single JSON or
+//! CSV file (relative path → description) and imports such a file back, so that
+//! descriptions can be moved independently of the storage backend.
+
+use crate::config::read_config;
+use crate::fs::{store_description, walk_dirs};
+use crate::storage::{self, DescriptionStorage};
+use crate::LsiArgs;
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::{Component, Path, PathBuf};
+
+/// Relative path used for the root directory itself.
+const ROOT_KEY: &str = ".";
+
+/// Supported file formats for exported descriptions.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum TransferFormat {
+    /// A JSON object mapping relative paths to descriptions.
+    Json,
+    /// A CSV file with `path` and `description` columns.
+    Csv,
+}
+
+impl TransferFormat {
+    /// Determines the format from an explicit name or, failing that, the file extension.
+    ///
+    /// # Arguments
+    ///
+    /// * `format` - An optional format name (`json` or `csv`).
+    /// * `file` - An optional file path whose extension is used as a hint.
+    ///
+    /// # Returns
+    ///
+    /// The selected format, `Json` by default.
+    pub fn detect(format: Option<&str>, file: Option<&str>) -> TransferFormat {
+        let hint = format.or_else(|| file.and_then(|f| Path::new(f).extension()?.to_str()));
+        match hint {
+            Some(f) if f.eq_ignore_ascii_case("csv") => TransferFormat::Csv,
+            _ => TransferFormat::Json,
+        }
+    }
+}
+
+/// Runs `lsi export` based on the provided arguments.
+///
+/// The descriptions under `args.path` are written to `args.transfer_file`,
+/// or to stdout if no file (or `-`) is given.
+///
+/// # Arguments
+///
+/// * `args` - A reference to the `LsiArgs` struct containing the current configuration.
+///
+/// # Returns
+///
+/// Returns a `Result` which is `Ok` if the operation is successful or an error otherwise.
+pub fn run_export(args: &LsiArgs) -> Result<()> {
+    let config = args.config_path.and_then(|path| read_config(path.to_string()));
+    let storage = storage::open(config.as_ref().and_then(|c| c.storage.as_ref()))?;
+    let format = TransferFormat::detect(args.transfer_format, args.transfer_file);
+
+    let descriptions = export(storage.as_ref(), Path::new(args.path))?;
+
+    match args.transfer_file {
+        Some(file) if file != "-" => {
+            let writer = File::create(file)
+                .with_context(|| format!("Failed to create export file: {}", file))?;
+            serialize(&descriptions, format, writer)?;
+            eprintln!("Success: Export {} descriptions to {}", descriptions.len(), file);
+        }
+        _ => serialize(&descriptions, format, io::stdout().lock())?,
+    }
+    Ok(())
+}
+
+/// Runs `lsi import` based on the provided arguments.
+///
+/// The descriptions read from `args.transfer_file` (or stdin for `-`) are applied
+/// relative to `args.path`. Entries whose path does not exist, is absolute
+/// or contains `..` are skipped.
+///
+/// # Arguments
+///
+/// * `args` - A reference to the `LsiArgs` struct containing the current configuration.
+///
+/// # Returns
+///
+/// Returns a `Result` which is `Ok` if the operation is successful or an error otherwise.
+pub fn run_import(args: &LsiArgs) -> Result<()> {
+    let config = args.config_path.and_then(|path| read_config(path.to_string()));
+    let storage = storage::open(config.as_ref().and_then(|c| c.storage.as_ref()))?;
+    let format = TransferFormat::detect(args.transfer_format, args.transfer_file);
+
+    let descriptions = match args.transfer_file {
+        Some(file) if file != "-" => {
+            let reader = File::open(file)
+                .with_context(|| format!("Failed to open import file: {}", file))?;
+            deserialize(format, reader)?
+        }
+        _ => deserialize(format, io::stdin().lock())?,
+    };
+
+    let skipped = import(storage.as_ref(), Path::new(args.path), &descriptions)?;
+    println!(
+        "Success: Import {} descriptions ({} skipped)",
+        descriptions.len() - skipped,
+        skipped
+    );
+    Ok(())
+}
+
+/// Collects every description under `root`, keyed by path relative to `root`.
+///
+/// # Arguments
+///
+/// * `storage` - The storage backend holding the descriptions.
+/// * `root` - The directory tree to export.
+///
+/// # Errors
+///
+/// Returns an error if `root` or one of its directories cannot be read.
+///
+/// # Returns
+///
+/// A map from relative path (`.` for `root` itself) to description.
+pub fn export(storage: &dyn DescriptionStorage, root: &Path) -> Result<BTreeMap<String, String>> {
+    let root = root
+        .canonicalize()
+        .with_context(|| format!("Failed to canonicalize path: {}", root.display()))?;
+
+    let mut descriptions = BTreeMap::new();
+    if let Ok(description) = storage.read(&root) {
+        descriptions.insert(ROOT_KEY.to_string(), description);
+    }
+    for dir in walk_dirs(&root)? {
+        for (path, description) in storage.list(&dir)? {
+            if let Ok(relative) = path.strip_prefix(&root) {
+                descriptions.insert(relative.to_string_lossy().to_string(), description);
+            }
+        }
+    }
+    Ok(descriptions)
+}
+
+/// Writes every description in `descriptions` to the path it names relative to `root`.
+///
+/// # Arguments
+///
+/// * `storage` - The storage backend to write to.
+/// * `root` - The directory the relative paths are resolved against.
+/// * `descriptions` - A map from relative path to description.
+///
+/// # Errors
+///
+/// Returns an error if a description cannot be written.
+///
+/// # Returns
+///
+/// The number of entries skipped because their path does not exist or leaves `root`.
+pub fn import(
+    storage: &dyn DescriptionStorage,
+    root: &Path,
+    descriptions: &BTreeMap<String, String>,
+) -> Result<usize> {
+    let mut skipped = 0;
+    for (relative, description) in descriptions {
+        if !is_within_root(Path::new(relative)) {
+            eprintln!("Skip: {} is outside of {}", relative, root.display());
+            skipped += 1;
+            continue;
+        }
+        let path: PathBuf = match relative.as_str() {
+            ROOT_KEY => root.to_path_buf(),
+            r => root.join(r),
+        };
+        if !path.exists() {
+            eprintln!("Skip: {} does not exist", path.display());
+            skipped += 1;
+            continue;
+        }
+        // Exported text is written back unchanged, so a literal `\n` stays as it is.
+        let location = store_description(storage, &path, description)?;
+        println!("Success: Write description to {}", &location);
+    }
+    Ok(skipped)
+}
+
+/// Checks that `relative` stays below the root it is joined to: it must not be absolute
+/// nor contain `..`.
+fn is_within_root(relative: &Path) -> bool {
+    relative
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+}
+
+/// Serializes descriptions in the given format.
+fn serialize<W: Write>(descriptions: &BTreeMap<String, String>, format: TransferFormat, mut writer: W) -> Result<()> {
+    match format {
+        TransferFormat::Json => {
+            serde_json::to_writer_pretty(&mut writer, descriptions)
+                .with_context(|| "Failed to write JSON")?;
+            writeln!(writer)?;
+        }
+        TransferFormat::Csv => {
+            let mut csv_writer = csv::Writer::from_writer(writer);
+            csv_writer.write_record(["path", "description"])?;
+            for (path, description) in descriptions {
+                csv_writer.write_record([path, description])?;
+            }
+            csv_writer.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// Deserializes descriptions in the given format.
+fn deserialize<R: Read>(format: TransferFormat, reader: R) -> Result<BTreeMap<String, String>> {
+    match format {
+        TransferFormat::Json => {
+            serde_json::from_reader(reader).with_context(|| "Failed to parse JSON")
+        }
+        TransferFormat::Csv => {
+            let mut descriptions = BTreeMap::new();
+            for record in csv::Reader::from_reader(reader).records() {
+                let record = record.with_context(|| "Failed to parse CSV")?;
+                if let (Some(path), Some(description)) = (record.get(0), record.get(1)) {
+                    descriptions.insert(path.to_string(), description.to_string());
+                }
+            }
+            Ok(descriptions)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::sidecar::SidecarStorage;
+
+    #[test]
+    fn import_skips_paths_outside_of_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("root");
+        std::fs::create_dir(&root).unwrap();
+        std::fs::write(root.join("a.txt"), "").unwrap();
+        std::fs::write(dir.path().join("secret"), "").unwrap();
+        let descriptions = BTreeMap::from([
+            ("a.txt".to_string(), "kept".to_string()),
+            ("../secret".to_string(), "escaped".to_string()),
+            (dir.path().join("secret").to_string_lossy().to_string(), "absolute".to_string()),
+        ]);
+
+        let storage = SidecarStorage;
+        assert_eq!(import(&storage, &root, &descriptions).unwrap(), 2);
+        assert_eq!(storage.read(&root.join("a.txt")).unwrap().trim_end(), "kept");
+        assert!(storage.read(&dir.path().join("secret")).is_err());
+    }
+
+    #[test]
+    fn export_then_import_keeps_descriptions_unchanged() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "").unwrap();
+        let storage = SidecarStorage;
+        storage.write(&dir.path().join("a.txt"), "path C:\\new\\data\nsecond line").unwrap();
+
+        let exported = export(&storage, dir.path()).unwrap();
+        storage.delete(&dir.path().join("a.txt")).unwrap();
+        assert_eq!(import(&storage, dir.path(), &exported).unwrap(), 0);
+        assert_eq!(export(&storage, dir.path()).unwrap(), exported);
+        assert_eq!(exported["a.txt"], "path C:\\new\\data\nsecond line");
+    }
+}
@@ -4,9 +4,49 @@
 
 use crate::colors::Colors;
 use crate::decoration;
+use crate::errors::LsiError;
 use crate::path::{LsiPath, LsiPathKind};
 use anyhow::Result;
 use std::path::PathBuf;
+use unicode_width::UnicodeWidthStr;
+
+/// The space between two columns of the grid layout.
+const GRID_GAP: usize = 2;
+
+/// The terminal width assumed when it cannot be determined.
+const DEFAULT_WIDTH: usize = 80;
+
+/// How the entries of a listing are laid out.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum Layout {
+    /// A tree under the directory header, with the full descriptions.
+    #[default]
+    Tree,
+    /// One entry per line with the first line of its description.
+    Oneline,
+    /// Columns like `ls`, with descriptions where they fit.
+    Grid,
+}
+
+impl Layout {
+    /// Parses a layout name from the config file.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - `tree`, `oneline` or `grid`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `LsiError::UnknownLayout` if no layout has that name.
+    pub fn parse(name: &str) -> Result<Layout> {
+        match name {
+            "tree" => Ok(Layout::Tree),
+            "oneline" => Ok(Layout::Oneline),
+            "grid" => Ok(Layout::Grid),
+            other => Err(LsiError::UnknownLayout(other.to_string()).into()),
+        }
+    }
+}
 
 /// Displays a list of paths with color and decorations.
 ///
@@ -17,8 +57,9 @@ use std::path::PathBuf;
 ///
 /// * `pathes` - A mutable reference to a vector of `LsiPath` to be displayed.
 /// * `colors` - A reference to the `Colors` struct for controlling the display colors.
-/// * `cwd` - The current working directory as a string slice.
+/// * `cwd` - The listed directory, printed as a header, or `None` for file arguments.
 /// * `desc_num` - An optional description number to select specific descriptions.
+/// * `columns` - The front-matter fields to show as aligned columns.
 ///
 /// # Errors
 ///
@@ -27,21 +68,317 @@ use std::path::PathBuf;
 pub fn display(
     pathes: &mut Vec<LsiPath>,
     colors: &Colors,
-    cwd: &str,
+    cwd: Option<&str>,
     desc_num: &Option<usize>,
+    columns: &[&str],
 ) -> Result<()> {
-    display_cwd(cwd, colors)?;
+    if let Some(cwd) = cwd {
+        display_cwd(cwd, colors)?;
+    }
     pathes.sort();
+    let widths = column_widths(pathes, columns);
     let length = pathes.len();
     let mut i = 0;
     for path in pathes {
         i += 1;
         let is_last = i == length;
-        display_a_line(&mut *path, is_last, colors, desc_num)?;
+        display_a_line(&mut *path, is_last, colors, desc_num, &widths)?;
     }
     Ok(())
 }
 
+/// Displays paths as given, one per line, followed by the first line of their description.
+///
+/// The descriptions are aligned after the widest path, which makes the output suitable
+/// for pickers such as `fzf --ansi`.
+///
+/// # Arguments
+///
+/// * `pathes` - The paths to be displayed, in display order.
+/// * `colors` - A reference to the `Colors` struct for controlling the display colors.
+/// * `columns` - The front-matter fields to show as aligned columns.
+///
+/// # Errors
+///
+/// This function returns an `anyhow::Result` to handle errors that might occur during display.
+pub fn display_flat(pathes: &[LsiPath], colors: &Colors, columns: &[&str]) -> Result<()> {
+    let names: Vec<String> = pathes.iter().map(|p| p.as_path().display().to_string()).collect();
+    display_aligned(pathes, names, colors, columns);
+    Ok(())
+}
+
+/// Displays the entries of a listing one per line, by name, followed by the first line
+/// of their description.
+///
+/// # Arguments
+///
+/// * `pathes` - The paths to be displayed, sorted in place.
+/// * `colors` - A reference to the `Colors` struct for controlling the display colors.
+/// * `cwd` - The listed directory, printed as a `dir:` header, or `None` for no header.
+/// * `columns` - The front-matter fields to show as aligned columns.
+///
+/// # Errors
+///
+/// This function returns an `anyhow::Result` to handle errors that might occur during display.
+pub fn display_oneline(
+    pathes: &mut [LsiPath],
+    colors: &Colors,
+    cwd: Option<&str>,
+    columns: &[&str],
+) -> Result<()> {
+    if let Some(cwd) = cwd {
+        println!("{}{}:{}", colors.current_dir, cwd, colors.end);
+    }
+    pathes.sort();
+    let names: Vec<String> = pathes.iter().map(|p| p.file_name().to_string()).collect();
+    display_aligned(pathes, names, colors, columns);
+    Ok(())
+}
+
+/// Displays the entries of a listing in columns, filled top to bottom like `ls`.
+///
+/// The number of columns is the largest one that fits the names into the terminal width;
+/// the remaining width is shared among the columns, and the first line of a description
+/// is shown after the name only where it fits into its column.
+///
+/// # Arguments
+///
+/// * `pathes` - The paths to be displayed, sorted in place.
+/// * `colors` - A reference to the `Colors` struct for controlling the display colors.
+/// * `cwd` - The listed directory, printed as a `dir:` header, or `None` for no header.
+///
+/// # Errors
+///
+/// This function returns an `anyhow::Result` to handle errors that might occur during display.
+pub fn display_grid(pathes: &mut [LsiPath], colors: &Colors, cwd: Option<&str>) -> Result<()> {
+    if let Some(cwd) = cwd {
+        println!("{}{}:{}", colors.current_dir, cwd, colors.end);
+    }
+    if pathes.is_empty() {
+        return Ok(());
+    }
+    pathes.sort();
+
+    let total = terminal_width();
+    let name_widths: Vec<usize> = pathes.iter().map(|p| p.len()).collect();
+    let (rows, widths) = grid_shape(&name_widths, total);
+    // Share the width left over by the names among the columns, for the descriptions.
+    let used: usize = widths.iter().sum::<usize>() + GRID_GAP * (widths.len() - 1);
+    let extra = total.saturating_sub(used) / widths.len();
+
+    for row in 0..rows {
+        let mut line = String::new();
+        for (col, width) in widths.iter().enumerate() {
+            let Some(path) = pathes.get(col * rows + row) else {
+                break;
+            };
+            let cell_width = width + extra;
+            let is_last = col + 1 == widths.len() || pathes.get((col + 1) * rows + row).is_none();
+            let color = match path.kind {
+                LsiPathKind::Dir => &colors.dir,
+                LsiPathKind::File => &colors.file,
+            };
+            let mut used = path.len();
+            line.push_str(&format!("{}{}{}", color, path.file_name(), colors.end));
+
+            let description = path
+                .get_plain_description()
+                .and_then(|d| d.lines().next().map(|l| l.trim().to_string()))
+                .unwrap_or_default();
+            if !description.is_empty() && used + 1 + description.width() <= cell_width {
+                line.push_str(&format!(" {}{}{}", colors.description, description, colors.end));
+                used += 1 + description.width();
+            }
+            if !is_last {
+                line.push_str(&" ".repeat(cell_width - used + GRID_GAP));
+            }
+        }
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+/// Finds the largest number of columns that fits the names into `total` columns.
+///
+/// # Arguments
+///
+/// * `name_widths` - The display widths of the names, in display order.
+/// * `total` - The available width.
+///
+/// # Returns
+///
+/// The number of rows and the width of each column.
+fn grid_shape(name_widths: &[usize], total: usize) -> (usize, Vec<usize>) {
+    for cols in (1..=name_widths.len()).rev() {
+        let rows = name_widths.len().div_ceil(cols);
+        let widths: Vec<usize> = name_widths
+            .chunks(rows)
+            .map(|chunk| chunk.iter().copied().max().unwrap_or(0))
+            .collect();
+        let used: usize = widths.iter().sum::<usize>() + GRID_GAP * (widths.len() - 1);
+        if used <= total || cols == 1 {
+            return (rows, widths);
+        }
+    }
+    (0, Vec::new())
+}
+
+/// Gets the width of the terminal from `$COLUMNS` or the terminal itself.
+fn terminal_width() -> usize {
+    std::env::var("COLUMNS")
+        .ok()
+        .and_then(|c| c.parse().ok())
+        .or_else(|| ratatui::crossterm::terminal::size().ok().map(|(w, _)| w as usize))
+        .filter(|w| *w > 0)
+        .unwrap_or(DEFAULT_WIDTH)
+}
+
+/// Prints each path under its name, aligned, followed by its git marker, field columns,
+/// tags and the first line of its description.
+fn display_aligned(pathes: &[LsiPath], names: Vec<String>, colors: &Colors, columns: &[&str]) {
+    let width = names.iter().map(|n| n.width()).max().unwrap_or(0);
+    let widths = column_widths(pathes, columns);
+
+    for (path, name) in pathes.iter().zip(names) {
+        let color = match path.kind {
+            LsiPathKind::Dir => &colors.dir,
+            LsiPathKind::File => &colors.file,
+        };
+        let description = path
+            .get_plain_description()
+            .and_then(|d| d.lines().next().map(|l| l.to_string()))
+            .unwrap_or_default();
+        let git_status = path
+            .get_git_status()
+            .map(|s| format!("{} ", s.marker(colors)))
+            .unwrap_or_default();
+        println!(
+            "{}{}{}{}  {}{}{}{}{}{}",
+            color,
+            name,
+            colors.end,
+            " ".repeat(width - name.width()),
+            git_status,
+            format_columns(path, &widths),
+            format_tags(path, colors),
+            colors.description,
+            description,
+            colors.end
+        );
+    }
+}
+
+/// Displays the description of a directory followed by its entries, one line each, in at
+/// most `max_lines` lines.
+///
+/// The last line tells how many lines were left out when the output is cut.
+///
+/// # Arguments
+///
+/// * `description` - The description of the listed directory, if any.
+/// * `pathes` - The entries to be displayed, in display order.
+/// * `colors` - A reference to the `Colors` struct for controlling the display colors.
+/// * `max_lines` - The maximum number of lines to print, or `0` for no limit.
+///
+/// # Errors
+///
+/// This function returns an `anyhow::Result` to handle errors that might occur during display.
+pub fn display_compact(
+    description: Option<&str>,
+    pathes: &[LsiPath],
+    colors: &Colors,
+    max_lines: usize,
+) -> Result<()> {
+    let mut lines: Vec<String> = description
+        .into_iter()
+        .flat_map(str::lines)
+        .map(|line| format!("{}{}{}", colors.description, line, colors.end))
+        .collect();
+
+    let width = pathes.iter().map(|p| p.len()).max().unwrap_or(0);
+    for path in pathes {
+        let (color, slash) = match path.kind {
+            LsiPathKind::Dir => (&colors.dir, "/"),
+            LsiPathKind::File => (&colors.file, " "),
+        };
+        let first_line = path
+            .get_plain_description()
+            .and_then(|d| d.lines().next().map(|l| l.to_string()))
+            .unwrap_or_default();
+        lines.push(format!(
+            "  {}{}{}{}{}  {}{}{}{}",
+            color,
+            path.file_name(),
+            colors.end,
+            slash,
+            " ".repeat(width - path.len()),
+            format_tags(path, colors),
+            colors.description,
+            first_line,
+            colors.end
+        ));
+    }
+
+    if max_lines > 0 && lines.len() > max_lines {
+        let kept = max_lines - 1;
+        let more = lines.len() - kept;
+        lines.truncate(kept);
+        lines.push(format!("  {}... {} more lines{}", colors.white, more, colors.end));
+    }
+    for line in lines {
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+/// Computes the display width of each field column.
+///
+/// # Arguments
+///
+/// * `pathes` - The paths to be displayed.
+/// * `columns` - The front-matter fields to show.
+///
+/// # Returns
+///
+/// Pairs of field name and the width of its widest `key=value` cell.
+fn column_widths(pathes: &[LsiPath], columns: &[&str]) -> Vec<(String, usize)> {
+    columns
+        .iter()
+        .map(|key| {
+            let width = pathes
+                .iter()
+                .map(|p| column_cell(p, key).width())
+                .max()
+                .unwrap_or(0);
+            (key.to_string(), width)
+        })
+        .collect()
+}
+
+/// Formats a single `key=value` cell, using `-` for a missing field.
+fn column_cell(path: &LsiPath, key: &str) -> String {
+    format!("{}={}", key, path.get_field(key).unwrap_or("-"))
+}
+
+/// Formats the field columns of a path, padded to the column widths.
+fn format_columns(path: &LsiPath, widths: &[(String, usize)]) -> String {
+    widths
+        .iter()
+        .map(|(key, width)| {
+            let cell = column_cell(path, key);
+            format!("{}{}  ", cell, " ".repeat(width - cell.width()))
+        })
+        .collect()
+}
+
+/// Formats the tags of a path as colored `#tag` badges, each followed by a space.
+fn format_tags(path: &LsiPath, colors: &Colors) -> String {
+    path.get_tags()
+        .iter()
+        .map(|tag| format!("{}#{}{} ", colors.tag, tag, colors.end))
+        .collect()
+}
+
 /// Displays the current working directory with colors.
 ///
 /// This function formats and prints the current working directory using the
@@ -96,6 +433,7 @@ fn display_cwd(cwd: &str, colors: &Colors) -> Result<()> {
 /// * `is_last` - A boolean indicating if this is the last path in the list.
 /// * `colors` - A reference to the `Colors` struct for controlling the display colors.
 /// * `desc_num` - An optional description number to select specific descriptions.
+/// * `widths` - The field columns to show and their widths.
 ///
 /// # Errors
 ///
@@ -106,8 +444,16 @@ fn display_a_line(
     is_last: bool,
     colors: &Colors,
     desc_num: &Option<usize>,
+    widths: &[(String, usize)],
 ) -> Result<()> {
-    decoration::run(&mut *path, colors, desc_num, &is_last)?;
+    let git_status = path.get_git_status().map(|s| s.marker(colors));
+    let columns_width = format_columns(path, widths).width() + git_status.as_ref().map_or(0, |_| 2);
+    let columns = match git_status {
+        Some(marker) => format!("{} {}", marker, format_columns(path, widths)),
+        None => format_columns(path, widths),
+    };
+    let badges = format_tags(path, colors);
+    decoration::run(&mut *path, colors, desc_num, &is_last, columns_width)?;
     let prefix_char = match is_last {
         true => "└──",
         false => "├──",
@@ -117,21 +463,25 @@ fn display_a_line(
             match &path.get_description() {
                 Some(_description) => {
                     println!(
-                        "{} {}{}{}\t/ {}",
+                        "{} {}{}{}\t{}/ {}{}",
                         prefix_char,
                         colors.dir,
                         path.file_name(),
                         colors.end,
+                        columns,
+                        badges,
                         _description
                     );
                 }
                 None => {
                     println!(
-                        "{} {}{}{}\t/ Dir",
+                        "{} {}{}{}\t{}/ {}Dir",
                         prefix_char,
                         colors.dir,
                         path.file_name(),
-                        colors.end
+                        colors.end,
+                        columns,
+                        badges
                     );
                 }
             };
@@ -140,21 +490,25 @@ fn display_a_line(
             match &path.get_description() {
                 Some(_description) => {
                     println!(
-                        "{} {}{}{}\t/ {}",
+                        "{} {}{}{}\t{}/ {}{}",
                         prefix_char,
                         colors.file,
                         path.file_name(),
                         colors.end,
+                        columns,
+                        badges,
                         _description
                     );
                 }
                 None => {
                     println!(
-                        "{} {}{}{}\t/ File",
+                        "{} {}{}{}\t{}/ {}File",
                         prefix_char,
                         colors.file,
                         path.file_name(),
-                        colors.end
+                        colors.end,
+                        columns,
+                        badges
                     );
                 }
             };
@@ -162,3 +516,32 @@ fn display_a_line(
     };
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grid_shape_uses_as_many_columns_as_fit() {
+        // 4 + 2 + 5 + 2 + 3 = 16 fits into 16, filled top to bottom.
+        assert_eq!(grid_shape(&[4, 5, 3], 16), (1, vec![4, 5, 3]));
+        assert_eq!(grid_shape(&[4, 5, 3], 15), (2, vec![5, 3]));
+        assert_eq!(grid_shape(&[2, 6, 2, 2, 2], 14), (2, vec![6, 2, 2]));
+        assert_eq!(grid_shape(&[2, 6, 2, 2, 2], 13), (3, vec![6, 2]));
+    }
+
+    #[test]
+    fn grid_shape_falls_back_to_one_column() {
+        assert_eq!(grid_shape(&[30, 40], 20), (2, vec![40]));
+        assert_eq!(grid_shape(&[], 80), (0, vec![]));
+    }
+
+    #[test]
+    fn layout_parse() {
+        assert_eq!(Layout::parse("tree").unwrap(), Layout::Tree);
+        assert_eq!(Layout::parse("oneline").unwrap(), Layout::Oneline);
+        assert_eq!(Layout::parse("grid").unwrap(), Layout::Grid);
+        let err = Layout::parse("table").unwrap_err();
+        assert!(matches!(err.downcast_ref::<LsiError>(), Some(LsiError::UnknownLayout(_))));
+    }
+}
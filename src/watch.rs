@@ -0,0 +1,243 @@
+//! This module implements `lsi --watch`, which keeps the listing on screen and redraws it
+//! whenever an entry is added, removed or renamed, or a description changes.
+//!
+//! Changes are watched with inotify (through the `notify` crate) on the listed
+//! directories, their `.file_description_lsi` directories, their child directories (for
+//! `.description.lsi`) and the parents of listed files. Files written inside child
+//! directories, such as checkpoints of a running job, do not trigger a redraw.
+
+use crate::errors::LsiError;
+use crate::path::LsiPathKind;
+use crate::storage::manifest::MANIFEST;
+use crate::storage::sidecar::{DIR_DESCRIPTION, FILE_DESCRIPTION_DIR};
+use crate::{fs, lsi, LsiArgs};
+use anyhow::Result;
+use notify::event::ModifyKind;
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+/// How long to wait for further changes before redrawing, so a burst of writes redraws once.
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// The longest a redraw is put off by a steady stream of changes.
+const MAX_DEBOUNCE: Duration = Duration::from_secs(1);
+
+/// Clears the terminal and moves the cursor to the top left.
+const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+
+/// Runs `lsi::run` and runs it again on every change until interrupted.
+///
+/// Errors of a single run (e.g. a listed path that was removed) are printed and watching
+/// goes on.
+///
+/// # Arguments
+///
+/// * `args` - A reference to the `LsiArgs` struct containing the current configuration.
+///
+/// # Errors
+///
+/// Returns an error if the watcher cannot be created.
+pub fn run(args: &LsiArgs) -> Result<()> {
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).map_err(|e| LsiError::FailedWatch(e.to_string()))?;
+
+    loop {
+        // The watched directories are collected again on every run, so new child
+        // directories and `.file_description_lsi` directories are picked up.
+        let listed = listed_dirs(args);
+        let watched = watched_dirs(args, &listed);
+        for dir in &watched {
+            let _ = watcher.watch(dir, RecursiveMode::NonRecursive);
+        }
+
+        print!("{}", CLEAR_SCREEN);
+        if let Err(e) = lsi::run(args) {
+            println!("Error: {:#}", e);
+        }
+        io::stdout().flush()?;
+
+        wait_for_change(&rx, &listed)?;
+        for dir in &watched {
+            let _ = watcher.unwatch(dir);
+        }
+    }
+}
+
+/// Collects the (canonical) directories whose entries are listed.
+///
+/// With `--paths`, the given paths are listed themselves, so their parents count instead.
+fn listed_dirs(args: &LsiArgs) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    for path in &args.paths {
+        let Ok(path) = Path::new(path).canonicalize() else {
+            // A missing path may appear later in its parent.
+            if let Some(parent) = Path::new(path).parent().and_then(existing_dir) {
+                dirs.push(parent);
+            }
+            continue;
+        };
+        if path.is_dir() && !args.flat {
+            dirs.push(path);
+        } else if let Some(parent) = path.parent() {
+            dirs.push(parent.to_path_buf());
+        }
+    }
+    dirs.sort();
+    dirs.dedup();
+    dirs
+}
+
+/// Canonicalizes `path`, treating an empty path as the current directory.
+fn existing_dir(path: &Path) -> Option<PathBuf> {
+    match path.as_os_str().is_empty() {
+        true => Path::new(".").canonicalize().ok(),
+        false => path.canonicalize().ok(),
+    }
+}
+
+/// Collects the directories to watch: the listed directories, their
+/// `.file_description_lsi` directories and their child directories.
+fn watched_dirs(args: &LsiArgs, listed: &[PathBuf]) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    for dir in listed {
+        dirs.push(dir.clone());
+        let sidecars = dir.join(FILE_DESCRIPTION_DIR);
+        if sidecars.is_dir() {
+            dirs.push(sidecars);
+        }
+        let children = fs::get_pathes(&dir.to_string_lossy(), &Some(LsiPathKind::Dir), &args.show_hidden, "p");
+        for child in children.into_iter().flatten() {
+            dirs.push(child.as_path().to_path_buf());
+        }
+    }
+    for path in &args.paths {
+        if let Ok(path) = Path::new(path).canonicalize() {
+            if path.is_dir() {
+                dirs.push(path);
+            }
+        }
+    }
+    dirs.sort();
+    dirs.dedup();
+    dirs
+}
+
+/// Blocks until a change that affects the listing happens, then waits for the burst to end.
+///
+/// Only changes that affect the listing extend the wait, and never beyond `MAX_DEBOUNCE`,
+/// so unrelated writes (e.g. checkpoints in a child directory) cannot hold off the redraw.
+///
+/// # Errors
+///
+/// Returns `LsiError::FailedWatch` if the watcher stops.
+fn wait_for_change(rx: &Receiver<notify::Result<Event>>, listed: &[PathBuf]) -> Result<()> {
+    loop {
+        let event = rx.recv().map_err(|e| LsiError::FailedWatch(e.to_string()))?;
+        if event.is_ok_and(|e| is_relevant(&e, listed)) {
+            break;
+        }
+    }
+    let deadline = Instant::now() + MAX_DEBOUNCE;
+    let mut quiet_until = Instant::now() + DEBOUNCE;
+    loop {
+        let timeout = quiet_until.min(deadline).saturating_duration_since(Instant::now());
+        match rx.recv_timeout(timeout) {
+            Ok(Ok(event)) if is_relevant(&event, listed) => quiet_until = Instant::now() + DEBOUNCE,
+            Ok(_) => {}
+            Err(RecvTimeoutError::Timeout) => return Ok(()),
+            Err(e) => return Err(LsiError::FailedWatch(e.to_string()).into()),
+        }
+        if Instant::now() >= quiet_until.min(deadline) {
+            return Ok(());
+        }
+    }
+}
+
+/// Checks whether an event changes the listing.
+///
+/// Any change of a description file counts, while other files only count when an entry
+/// of a listed directory is created, removed, renamed or has its attributes (such as the
+/// `user.lsi.description` attribute) changed.
+fn is_relevant(event: &Event, listed: &[PathBuf]) -> bool {
+    if event.paths.iter().any(|p| is_description_file(p)) {
+        return !matches!(event.kind, EventKind::Access(_));
+    }
+    let is_entry_change = matches!(
+        event.kind,
+        EventKind::Create(_)
+            | EventKind::Remove(_)
+            | EventKind::Modify(ModifyKind::Name(_))
+            | EventKind::Modify(ModifyKind::Metadata(_))
+    );
+    is_entry_change
+        && event
+            .paths
+            .iter()
+            .any(|p| p.parent().is_some_and(|parent| listed.iter().any(|d| d == parent)))
+}
+
+/// Checks whether `path` is a sidecar or manifest description file.
+fn is_description_file(path: &Path) -> bool {
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+    let in_sidecar_dir = path
+        .parent()
+        .and_then(|p| p.file_name())
+        .is_some_and(|n| n == FILE_DESCRIPTION_DIR);
+    name == DIR_DESCRIPTION || name == MANIFEST || (in_sidecar_dir && name.ends_with(".lsi"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{AccessKind, CreateKind, DataChange, MetadataKind, RemoveKind};
+    use std::thread;
+
+    fn event(kind: EventKind, path: &str) -> Event {
+        Event::new(kind).add_path(PathBuf::from(path))
+    }
+
+    #[test]
+    fn is_relevant_counts_entries_of_listed_directories() {
+        let listed = [PathBuf::from("/job")];
+        let data = EventKind::Modify(ModifyKind::Data(DataChange::Any));
+        assert!(is_relevant(&event(EventKind::Create(CreateKind::File), "/job/f.txt"), &listed));
+        assert!(is_relevant(&event(EventKind::Remove(RemoveKind::Folder), "/job/ckpt"), &listed));
+        assert!(is_relevant(&event(EventKind::Modify(ModifyKind::Metadata(MetadataKind::Extended)), "/job/f.txt"), &listed));
+        assert!(!is_relevant(&event(data, "/job/f.txt"), &listed));
+        // Checkpoints written inside a child directory do not change the listing.
+        assert!(!is_relevant(&event(EventKind::Create(CreateKind::File), "/job/ckpt/step.bin"), &listed));
+    }
+
+    #[test]
+    fn is_relevant_counts_description_files() {
+        let listed = [PathBuf::from("/job")];
+        let data = EventKind::Modify(ModifyKind::Data(DataChange::Any));
+        assert!(is_relevant(&event(data, "/job/.file_description_lsi/.f.txt.lsi"), &listed));
+        assert!(is_relevant(&event(data, "/job/ckpt/.description.lsi"), &listed));
+        assert!(is_relevant(&event(data, "/job/ckpt/.lsi.toml"), &listed));
+        assert!(!is_relevant(&event(EventKind::Access(AccessKind::Any), "/job/ckpt/.description.lsi"), &listed));
+        assert!(!is_relevant(&event(data, "/job/ckpt/notes.lsi"), &listed));
+    }
+
+    #[test]
+    fn wait_for_change_is_not_held_off_by_a_stream_of_changes() {
+        let (tx, rx) = mpsc::channel();
+        let listed = [PathBuf::from("/job")];
+        thread::spawn(move || {
+            for _ in 0..100 {
+                let change = event(EventKind::Create(CreateKind::File), "/job/.description.lsi");
+                if tx.send(Ok(change)).is_err() {
+                    return;
+                }
+                thread::sleep(Duration::from_millis(50));
+            }
+        });
+
+        let start = Instant::now();
+        wait_for_change(&rx, &listed).unwrap();
+        assert!(start.elapsed() < MAX_DEBOUNCE + Duration::from_millis(500));
+    }
+}